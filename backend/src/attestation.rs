@@ -0,0 +1,83 @@
+use ic_cdk::api::management_canister::ecdsa::{
+    ecdsa_public_key, sign_with_ecdsa, EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyArgument, SignWithEcdsaArgument,
+};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// Key of the local replica; deployments on the IC set "key_1" or "test_key_1" through the canister config
+pub static DEFAULT_KEY_NAME: &str = "dfx_test_key";
+pub static ALGORITHM: &str = "ecdsa-secp256k1-sha256";
+// Every threshold signature is paid for by the canister, so each caller gets a daily quota
+static SIGNATURES_PER_WINDOW: u32 = 10;
+static QUOTA_WINDOW_NANOS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+thread_local! {
+    // (key name, SEC1 compressed public key); the derived key never changes for a given key name
+    static PUBLIC_KEY_CACHE: RefCell<Option<(String, Vec<u8>)>> = const { RefCell::new(None) };
+    // caller -> (start of the current quota window, signatures requested in it); reset by upgrades
+    static SIGNING_QUOTA: RefCell<HashMap<String, (u64, u32)>> = RefCell::new(HashMap::new());
+}
+
+fn key_id(key_name: &str) -> EcdsaKeyId {
+    EcdsaKeyId {
+        curve: EcdsaCurve::Secp256k1,
+        name: key_name.to_string(),
+    }
+}
+
+fn derivation_path() -> Vec<Vec<u8>> {
+    vec![b"proofly-attestation".to_vec()]
+}
+
+pub async fn public_key(key_name: &str) -> Result<Vec<u8>, &'static str> {
+    let cached = PUBLIC_KEY_CACHE.with(|cache| {
+        cache.borrow().as_ref()
+            .filter(|(cached_name, _)| cached_name == key_name)
+            .map(|(_, public_key)| public_key.clone())
+    });
+    if let Some(public_key) = cached {
+        return Ok(public_key);
+    }
+
+    let (response,) = ecdsa_public_key(EcdsaPublicKeyArgument {
+        canister_id: None,
+        derivation_path: derivation_path(),
+        key_id: key_id(key_name),
+    })
+    .await
+    .map_err(|_| "Attestation key is currently unavailable")?;
+
+    PUBLIC_KEY_CACHE.with(|cache| {
+        *cache.borrow_mut() = Some((key_name.to_string(), response.public_key.clone()));
+    });
+    Ok(response.public_key)
+}
+
+// Must be called before every `sign` made on behalf of a caller; the quota is spent even if signing fails
+pub fn take_signing_quota(caller_id: &str, now: u64) -> Result<(), &'static str> {
+    SIGNING_QUOTA.with(|quota| {
+        let mut quota = quota.borrow_mut();
+        quota.retain(|_, (window_start, _)| now < window_start.saturating_add(QUOTA_WINDOW_NANOS));
+
+        let (_, count) = quota.entry(caller_id.to_string()).or_insert((now, 0));
+        if *count >= SIGNATURES_PER_WINDOW {
+            return Err("Signing quota exceeded, try again tomorrow");
+        }
+        *count += 1;
+        Ok(())
+    })
+}
+
+// Signs SHA-256(message); the result is the 64-byte r || s signature
+pub async fn sign(key_name: &str, message: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let (response,) = sign_with_ecdsa(SignWithEcdsaArgument {
+        message_hash: Sha256::digest(message).to_vec(),
+        derivation_path: derivation_path(),
+        key_id: key_id(key_name),
+    })
+    .await
+    .map_err(|_| "Failed to sign the attestation")?;
+
+    Ok(response.signature)
+}
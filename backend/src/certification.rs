@@ -0,0 +1,54 @@
+use ic_certification::{AsHashTree, RbTree};
+use serde::Serialize;
+use std::cell::RefCell;
+
+pub static PROOFS_LABEL: &[u8] = b"proofs";
+pub static COMPANIES_LABEL: &[u8] = b"companies";
+
+type LabeledTree = RbTree<&'static [u8], RbTree<Vec<u8>, Vec<u8>>>;
+
+thread_local! {
+    // label -> (key -> candid-encoded value); rebuilt from stable memory after upgrades
+    static TREE: RefCell<LabeledTree> = RefCell::new(empty_tree());
+}
+
+fn empty_tree() -> LabeledTree {
+    let mut tree = RbTree::new();
+    tree.insert(PROOFS_LABEL, RbTree::new());
+    tree.insert(COMPANIES_LABEL, RbTree::new());
+    tree
+}
+
+// Inserts (Some) or removes (None) the entry without touching the certified data
+pub fn put(label: &'static [u8], key: Vec<u8>, value: Option<Vec<u8>>) {
+    TREE.with(|tree| {
+        tree.borrow_mut().modify(label, |entries| match value {
+            Some(value) => entries.insert(key, value),
+            None => entries.delete(&key),
+        });
+    });
+}
+
+pub fn publish() {
+    TREE.with(|tree| {
+        ic_cdk::api::set_certified_data(&tree.borrow().root_hash());
+    });
+}
+
+// The exact bytes stored under /<label>/<key>, which its witness commits to
+pub fn get(label: &'static [u8], key: &[u8]) -> Option<Vec<u8>> {
+    TREE.with(|tree| tree.borrow().get(label).and_then(|entries| entries.get(key)).cloned())
+}
+
+// CBOR-encoded witness for /<label>/<key>, or a proof of absence
+pub fn witness(label: &'static [u8], key: &[u8]) -> Vec<u8> {
+    TREE.with(|tree| {
+        let tree = tree.borrow();
+        let witness = tree.nested_witness(label, |entries| entries.witness(key));
+
+        let mut serializer = serde_cbor::ser::Serializer::new(Vec::new());
+        serializer.self_describe().expect("Failed to write the CBOR tag");
+        witness.serialize(&mut serializer).expect("Failed to encode the witness");
+        serializer.into_inner()
+    })
+}
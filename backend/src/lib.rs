@@ -1,18 +1,56 @@
-use candid::{CandidType,Decode,Deserialize,Encode};
+mod attestation;
+mod certification;
+mod credential;
+mod token;
+
+use candid::{CandidType,Decode,Deserialize,Encode,Principal};
+use serde::Serialize;
 use ic_cdk::caller;
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
-use ic_stable_structures::{DefaultMemoryImpl,StableBTreeMap,Storable,BoundedStorable};
-use std::{cell::RefCell,borrow::Cow};
+use ic_stable_structures::{DefaultMemoryImpl,StableBTreeMap,StableCell,Storable,BoundedStorable};
+use std::{cell::{Cell, RefCell},borrow::Cow,time::Duration};
 use sha2::{Digest, Sha256};
+use hmac::{Hmac, Mac};
+use subtle::ConstantTimeEq;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::{RngCore, SeedableRng};
 
 type Memory = VirtualMemory<DefaultMemoryImpl>;
-static PROOF_LENTGH: u32 = 10;
+type EmploymentKey = (StorableString, StorableString); // (compID, empID)
+type PositionKey = (EmploymentKey, (u32, u32)); // (employment, (nth employment, nth ended position))
+static PROOF_LENTGH: u32 = 10; // random prefix of legacy proof codes
+static PROOF_SECRET_BYTES: usize = 10;
+static PROOF_HASH_VERSION: u8 = 1; // HMAC-SHA256 with PROOF_KEY; proofs without a version use plain SHA-256
+static DEFAULT_PROOF_VALIDITY_SECS: u64 = 24 * 60 * 60;
+static MAX_PROOF_VALIDITY_SECS: u64 = 365 * 24 * 60 * 60; // upper bound for any proof, whatever the company allows
+static MAX_PROOF_VERIFICATIONS: u32 = 100;
+static DEFAULT_PAGE_LIMIT: u64 = 20;
+static MAX_PAGE_LIMIT: u64 = 100;
+static MAX_PURPOSE_LENGTH: usize = 200;
+static MAX_POSITION_LENGTH: usize = 100;
+static MAX_UNIT_NAME_LENGTH: usize = 100;
+static MAX_UNIT_DEPTH: usize = 8;
+static MAX_UNIT_PATH_LENGTH: usize = 250; // proofs store the path, and are bounded by the Proof MAX_SIZE
+static UNIT_PATH_SEPARATOR: &str = " / ";
+static MAX_UNIT_ADMINS: usize = 5;
+static MAX_CITY_LENGTH: usize = 100;
+static MAX_WEEKLY_HOURS: u8 = 80;
+static INVITE_VALIDITY_SECS: u64 = 7 * 24 * 60 * 60;
+static CLAIM_VALIDITY_SECS: u64 = 30 * 24 * 60 * 60; // unreviewed claims are dropped afterwards
+static MAX_CURRENT_POSITIONS: usize = 12; // held at once, bounded by the Employment MAX_SIZE; ended ones move to POSITION_HISTORY
+static MAX_ATTESTED_POSITIONS: usize = 5; // per proof, which stores its titles twice and is bounded by the Proof MAX_SIZE
+static MAX_ATTESTED_POSITION_BYTES: usize = 300;
+static DEFAULT_PROOF_RETENTION_SECS: u64 = 30 * 24 * 60 * 60;
+static CLEANUP_INTERVAL_SECS: u64 = 60 * 60;
+static DEFAULT_MAX_FAILED_ATTEMPTS: u32 = 5;
+static PROOF_LOCKOUT_SECS: u64 = 15 * 60; // how long a proof stays locked after too many mismatches
+static CLEANUP_INSTRUCTION_BUDGET: u64 = 2_000_000_000; // per cleanup task, well below the per-message limit of timer callbacks
 thread_local! {
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> =
         RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
 
     static COMPANY_EMPLOYEES: RefCell<StableBTreeMap<StorableString, CompanyEmployeeList, Memory>> = RefCell::new(
-        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(0)))) // compID -> arr of empID
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(0)))) // compID -> arr of empID, legacy; moved into EMPLOYMENTS on upgrade
     );
 
     static EMPLOYEE_COMPANIES: RefCell<StableBTreeMap<StorableString, IDList, Memory>> = RefCell::new(
@@ -28,13 +66,84 @@ thread_local! {
     static EMPLOYEE_MAP: RefCell<StableBTreeMap<StorableString, Employee, Memory>> = RefCell::new(
         StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(4)))) // EmpID -> emp
     );
+    static LEGACY_PROOF_MAP: RefCell<StableBTreeMap<u128, LegacyProof, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(5)))) // ProofID -> Proof (512 bytes, drained on upgrade)
+    );
+    static NEXT_PROOF_ID: RefCell<StableCell<u128, Memory>> = RefCell::new(
+        StableCell::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(6))), 0) // next free ProofID
+            .expect("Failed to initialize the proof ID counter")
+    );
+    static EMPLOYEE_PROOFS: RefCell<StableBTreeMap<(StorableString, u128), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(7)))) // (empID, ProofID) -> ()
+    );
+    static COMPANY_PROOFS: RefCell<StableBTreeMap<(StorableString, u128), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(8)))) // (compID, ProofID) -> ()
+    );
+    static PROOF_RECEIPTS: RefCell<StableBTreeMap<(u128, u32), VerificationReceipt, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(9)))) // (ProofID, nth verification) -> receipt
+    );
     static PROOF_MAP: RefCell<StableBTreeMap<u128, Proof, Memory>> = RefCell::new(
-        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(5)))) // ProofID -> Proof
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(10)))) // ProofID -> Proof
+    );
+    static CONFIG: RefCell<StableCell<CanisterConfig, Memory>> = RefCell::new(
+        StableCell::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(11))), CanisterConfig::default())
+            .expect("Failed to initialize the canister config")
+    );
+
+    static PROOF_KEY: RefCell<StableCell<Vec<u8>, Memory>> = RefCell::new(
+        StableCell::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(12))), Vec::new()) // HMAC key for proof codes, empty until fetched
+            .expect("Failed to initialize the proof key")
+    );
+
+    static EMPLOYMENTS: RefCell<StableBTreeMap<(StorableString, StorableString), Employment, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(13)))) // (compID, empID) -> employment, current and former
+    );
+    static EMPLOYEE_EMPLOYMENTS: RefCell<StableBTreeMap<(StorableString, StorableString), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(14)))) // (empID, compID) -> ()
+    );
+    static PAST_EMPLOYMENTS: RefCell<StableBTreeMap<(EmploymentKey, u32), Employment, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(24)))) // ((compID, empID), nth employment) -> employment ended before a re-hire
+    );
+    static POSITION_HISTORY: RefCell<StableBTreeMap<PositionKey, EmploymentPosition, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(25)))) // position ended while its employment went on
+    );
+
+    static INVITES: RefCell<StableBTreeMap<(StorableString, StorableString), Invite, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(15)))) // (compID, empID) -> pending invite
     );
-    static NEXT_PROOF_ID: RefCell<u128> = RefCell::new(0);
+    static EMPLOYEE_INVITES: RefCell<StableBTreeMap<(StorableString, StorableString), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(16)))) // (empID, compID) -> ()
+    );
+
+    static EMPLOYMENT_CLAIMS: RefCell<StableBTreeMap<(StorableString, StorableString), EmploymentClaim, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(17)))) // (compID, empID) -> claim awaiting review
+    );
+    static EMPLOYEE_CLAIMS: RefCell<StableBTreeMap<(StorableString, StorableString), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(18)))) // (empID, compID) -> ()
+    );
+
+    static COMPANY_NOTIFICATIONS: RefCell<StableBTreeMap<(StorableString, u64), CompanyNotification, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(19)))) // (compID, NotificationID) -> notification for the admins
+    );
+    static NEXT_NOTIFICATION_ID: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(20))), 0) // next free NotificationID
+            .expect("Failed to initialize the notification ID counter")
+    );
+
+    static ORG_UNITS: RefCell<StableBTreeMap<(StorableString, u64), OrgUnit, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(21)))) // (compID, UnitID) -> department or team
+    );
+    static NEXT_UNIT_ID: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(22))), 0) // next free UnitID
+            .expect("Failed to initialize the unit ID counter")
+    );
+
+    static CLEANUP_CURSOR: Cell<u128> = const { Cell::new(0) }; // next ProofID the cleanup timer looks at
+    static INVITE_CLEANUP_CURSOR: RefCell<Option<EmploymentKey>> = const { RefCell::new(None) }; // next invite it looks at
+    static CLAIM_CLEANUP_CURSOR: RefCell<Option<EmploymentKey>> = const { RefCell::new(None) }; // next claim it looks at
 }
 
-#[derive(CandidType, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[derive(CandidType, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
 pub struct StorableString {
     pub value: String,
 }
@@ -49,7 +158,10 @@ pub struct CompanyEmployee {
 pub struct CompanyEmployeeWithName {
     pub employee_id: String,
     pub employee_name: String,
-    pub position: String,
+    pub position: String, // current positions, comma separated
+    pub positions: Vec<String>,
+    pub unit: Option<String>, // path of the employee's unit, e.g. "Engineering / Platform team"
+    pub attributes: EmploymentAttributes,
 }
 
 pub struct IDList {
@@ -60,13 +172,16 @@ pub struct CompanyEmployeeList {
     pub employees: Vec<CompanyEmployee>,
 }
 
+// Proofs as stored before PROOF_MAP outgrew its original 512-byte bound
+struct LegacyProof(Proof);
+
 
 impl Storable for IDList {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(Encode!(&self.ids).unwrap())
     }
 
-    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
         Self {
             ids: Decode!(bytes.as_ref(), Vec<String>).unwrap(),
         }
@@ -74,11 +189,11 @@ impl Storable for IDList {
 }
 
 impl Storable for CompanyEmployeeList {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(Encode!(&self.employees).unwrap())
     }
 
-    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
         Self {
             employees: Decode!(bytes.as_ref(), Vec<CompanyEmployee>).unwrap(),
         }
@@ -86,41 +201,131 @@ impl Storable for CompanyEmployeeList {
 }
 
 impl Storable for Company {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
-    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
         Decode!(bytes.as_ref(), Company).unwrap()
     }
 }
 
 impl Storable for Employee {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
-    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
         Decode!(bytes.as_ref(), Employee).unwrap()
     }
 }
 
 impl Storable for Proof {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
-    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
         Decode!(bytes.as_ref(), Proof).unwrap()
     }
 }
 
+impl Storable for Employment {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(bytes.as_ref(), Employment).unwrap()
+    }
+}
+
+impl Storable for EmploymentPosition {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(bytes.as_ref(), EmploymentPosition).unwrap()
+    }
+}
+
+impl Storable for Invite {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(bytes.as_ref(), Invite).unwrap()
+    }
+}
+
+impl Storable for EmploymentClaim {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(bytes.as_ref(), EmploymentClaim).unwrap()
+    }
+}
+
+impl Storable for CompanyNotification {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(bytes.as_ref(), CompanyNotification).unwrap()
+    }
+}
+
+impl Storable for OrgUnit {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(bytes.as_ref(), OrgUnit).unwrap()
+    }
+}
+
+impl Storable for VerificationReceipt {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(bytes.as_ref(), VerificationReceipt).unwrap()
+    }
+}
+
+impl Storable for LegacyProof {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        self.0.to_bytes()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Self(Proof::from_bytes(bytes))
+    }
+}
+
+impl Storable for CanisterConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(bytes.as_ref(), CanisterConfig).unwrap()
+    }
+}
+
 impl Storable for StorableString {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(Encode!(&self.value).unwrap())
     }
 
-    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
         Self{
             value: Decode!(bytes.as_ref(), String).unwrap(),
         }
@@ -149,6 +354,46 @@ impl BoundedStorable for Employee {
 }
 
 impl BoundedStorable for Proof {
+    const MAX_SIZE: u32 = 2048;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl BoundedStorable for LegacyProof {
+    const MAX_SIZE: u32 = 512;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl BoundedStorable for Employment {
+    const MAX_SIZE: u32 = 4096;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl BoundedStorable for EmploymentPosition {
+    const MAX_SIZE: u32 = 512;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl BoundedStorable for Invite {
+    const MAX_SIZE: u32 = 512;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl BoundedStorable for EmploymentClaim {
+    const MAX_SIZE: u32 = 512;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl BoundedStorable for CompanyNotification {
+    const MAX_SIZE: u32 = 512;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl BoundedStorable for OrgUnit {
+    const MAX_SIZE: u32 = 1024;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl BoundedStorable for VerificationReceipt {
     const MAX_SIZE: u32 = 512;
     const IS_FIXED_SIZE: bool = false;
 }
@@ -167,6 +412,15 @@ struct Company {
     admin_id: String,
     created_at: u64,
     is_active: bool,
+    max_proof_validity_secs: Option<u64>, // None -> only MAX_PROOF_VALIDITY_SECS applies
+}
+
+// Canister-wide settings managed by the controllers; None -> built-in default
+#[derive(CandidType, Deserialize, Clone, Default)]
+pub struct CanisterConfig {
+    pub proof_retention_secs: Option<u64>, // how long proofs are kept once they can no longer be verified
+    pub max_failed_attempts: Option<u32>, // code mismatches before a proof is locked for PROOF_LOCKOUT_SECS
+    pub attestation_key_name: Option<String>, // threshold ECDSA key signing attestations
 }
 
 #[derive(CandidType, Deserialize, Clone)]
@@ -175,407 +429,2529 @@ struct Employee {
     full_name: String,
 }
 
+// EMPLOYEE_COMPANIES only lists current employments; records without an end date form the company roster
 #[derive(CandidType, Deserialize, Clone)]
-struct Proof {
-    code: String,
-    company_username: String,
-    employee_id: String,
+struct Employment {
+    position: String, // latest assigned position; `positions` is authoritative once set
+    start_date: Option<u64>, // None -> the employment predates employment history
+    end_date: Option<u64>, // None -> currently employed
+    ended_by: Option<String>, // principal of the admin who ended it, or of the employee who left
+    positions: Option<Vec<EmploymentPosition>>, // None -> `position`, held for the whole employment; see POSITION_HISTORY
+    unit_id: Option<u64>, // department or team in ORG_UNITS
+    attributes: Option<EmploymentAttributes>,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct EmploymentPosition {
+    pub title: String,
+    pub start_date: Option<u64>,
+    pub end_date: Option<u64>, // None -> still held
+    pub added_by: Option<String>, // principal of the admin who assigned it
+    pub ended_by: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy)]
+pub struct DateRange {
+    pub from: u64,
+    pub to: u64,
+}
+
+// Created by add_employee for anyone not yet employed; nothing is attested until it is accepted
+#[derive(CandidType, Deserialize, Clone)]
+struct Invite {
     position: String,
+    start_date: Option<u64>,
+    invited_by: String,
     created_at: u64,
     expires_at: u64,
-    is_used: bool,
 }
 
 #[derive(CandidType, Deserialize, Clone)]
-pub struct ProofResult {
+pub struct InviteSummary {
     pub company_username: String,
     pub company_name: String,
     pub employee_id: String,
     pub employee_name: String,
     pub position: String,
+    pub start_date: Option<u64>,
+    pub invited_by: String,
     pub created_at: u64,
+    pub expires_at: u64,
 }
 
-async fn generate_random_code(length: usize) -> String {
-    let chars = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz0123456789".chars().collect::<Vec<char>>();
-    
-    let random_bytes = match ic_cdk::api::management_canister::main::raw_rand().await {
-        Ok((bytes,)) => bytes,
-        Err(_) => {
-            ic_cdk::api::time().to_le_bytes().to_vec()
-        }
-    };
-    
-    let mut result = String::new();
-    for i in 0..length {
-        let byte_index = i % random_bytes.len();
-        let char_index = (random_bytes[byte_index] as usize) % chars.len();
-        result.push(chars[char_index]);
-    }
-    
-    result
+#[derive(CandidType, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum EmploymentType {
+    FullTime,
+    PartTime,
+    Contractor,
+    Intern,
 }
 
-fn is_company_admin(admin_principal: &String, company_username: &String) -> bool {
-    let admin_key = StorableString { value: admin_principal.clone() };
+#[derive(CandidType, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum SeniorityLevel {
+    Entry,
+    Junior,
+    Mid,
+    Senior,
+    Lead,
+    Principal,
+    Executive,
+}
 
-    EMPLOYEE_COMPANIES_ADMIN.with(|comp| {
-        let map = comp.borrow();
-        if let Some(comp_list) = map.get(&admin_key) {
-            return comp_list.ids.contains(company_username);
-        }
-        false
-    })
+#[derive(CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct WorkLocation {
+    pub country: String, // ISO 3166-1 alpha-2 code, e.g. "DE"
+    pub city: Option<String>,
 }
 
-fn is_works_on(user_id: &String, company_username: &String) -> bool {
-    let user_key = StorableString { value: user_id.clone() };
+// Typed terms of the employment, set by company or unit admins; None -> not recorded
+#[derive(CandidType, Deserialize, Clone, Default)]
+pub struct EmploymentAttributes {
+    pub employment_type: Option<EmploymentType>,
+    pub location: Option<WorkLocation>,
+    pub seniority: Option<SeniorityLevel>,
+    pub weekly_hours: Option<u8>,
+}
 
-    EMPLOYEE_COMPANIES.with(|comp| {
-        let map = comp.borrow();
-        if let Some(comp_list) = map.get(&user_key) {
-            return comp_list.ids.contains(company_username);
-        }
-        false
-    })
+// Submitted by the employee ("I work here as ... since ..."), waits in the company's review queue
+#[derive(CandidType, Deserialize, Clone)]
+struct EmploymentClaim {
+    position: String,
+    start_date: Option<u64>,
+    submitted_at: u64,
+    expires_at: Option<u64>, // None -> submitted before claims expired, CLAIM_VALIDITY_SECS after submitted_at
 }
 
-#[ic_cdk::update]
-fn set_full_name(full_name: String) -> Result<(), &'static str> {
-    // Validate input
-    if full_name.trim().is_empty() {
-        return Err("Full name cannot be empty");
-    }
+#[derive(CandidType, Deserialize, Clone)]
+pub struct EmploymentClaimSummary {
+    pub company_username: String,
+    pub company_name: String,
+    pub employee_id: String,
+    pub employee_name: String,
+    pub position: String,
+    pub start_date: Option<u64>,
+    pub submitted_at: u64,
+    pub expires_at: u64,
+}
 
-    let caller_principal = ic_cdk::caller();
-    let user_id = caller_principal.to_text();
-    let key = StorableString { value: user_id.clone() };
+#[derive(CandidType, Deserialize, Clone)]
+pub enum ClaimReview {
+    Approve,
+    Amend { position: Option<String>, start_date: Option<u64> }, // approve with corrections, None keeps the claimed value
+    Reject,
+}
 
-    EMPLOYEE_MAP.with(|emp_map| {
-        let mut map = emp_map.borrow_mut();
-        let employee = if let Some(mut existing) = map.get(&key) {
-            existing.full_name = full_name.clone();
-            existing
-        } else {
-            Employee { id: user_id.clone(), full_name: full_name.clone() }
-        };
-        map.insert(key, employee);
-    });
+#[derive(CandidType, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    EmployeeLeft,
+}
 
-    Ok(())
+// Department or team; admins of a unit manage the employees of the unit and of every unit below it
+#[derive(CandidType, Deserialize, Clone)]
+struct OrgUnit {
+    name: String,
+    parent_id: Option<u64>, // None -> top-level unit
+    admins: Vec<String>,
+    created_at: u64,
 }
 
-fn get_employee_name_by_id(emp_id: &String) -> String {
-    let key = StorableString { value: emp_id.clone() };
-    EMPLOYEE_MAP.with(|emp_map| {
-        let map_ref = emp_map.borrow();
-        if let Some(employee) = map_ref.get(&key) {
-            employee.full_name.clone()
-        } else {
-            emp_id.clone()
-        }
-    })
+#[derive(CandidType, Deserialize, Clone)]
+pub struct UnitSummary {
+    pub unit_id: u64,
+    pub name: String,
+    pub parent_id: Option<u64>,
+    pub path: String, // names from the top-level unit down, e.g. "Engineering / Platform team"
+    pub admins: Vec<String>,
 }
 
-#[ic_cdk::query]
-fn get_my_name() -> Result<String, &'static str> {
-    let caller_principal = ic_cdk::caller();
-    let user_id = caller_principal.to_text();
-    let key = StorableString { value: user_id };
+// Something the company admins should know about; kept until dismissed or the company is deleted
+#[derive(CandidType, Deserialize, Clone)]
+struct CompanyNotification {
+    kind: NotificationKind,
+    employee_id: String,
+    created_at: u64,
+}
 
-    EMPLOYEE_MAP.with(|emp_map| {
-        let map_ref = emp_map.borrow();
-        if let Some(employee) = map_ref.get(&key) {
-            if employee.full_name.trim().is_empty() {
-                Err("Name not set")
-            } else {
-                Ok(employee.full_name.clone())
-            }
-        } else {
-            Err("Name not set")
-        }
-    })
+#[derive(CandidType, Deserialize, Clone)]
+pub struct CompanyNotificationSummary {
+    pub notification_id: u64,
+    pub kind: NotificationKind,
+    pub employee_id: String,
+    pub employee_name: String,
+    pub created_at: u64,
 }
 
-#[ic_cdk::update]
-async fn generate_proof(company_username:String) -> Result<String, &'static str> {
-    let caller_principal = ic_cdk::caller();
-    let user_id: String = caller_principal.to_text();
+#[derive(CandidType, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum EmploymentStatus {
+    Current,
+    Former,
+}
 
-    //check if user_id works in company_username or not
-    if !is_works_on(&user_id,&company_username) {
-        return Err("Caller is not works in this company");
-    }
+#[derive(CandidType, Deserialize, Clone)]
+pub struct EmploymentPeriod {
+    pub status: EmploymentStatus,
+    pub start_date: Option<u64>,
+    pub end_date: Option<u64>,
+}
 
-    // Get employee's position in this company
-    let position = COMPANY_EMPLOYEES.with(|map| {
-        let map_ref = map.borrow();
-        let comp_key = StorableString { value: company_username.clone() };
-        
-        if let Some(emp_list) = map_ref.get(&comp_key) {
-            if let Some(emp) = emp_list.employees.iter().find(|e| e.employee_id == user_id) {
-                return emp.position.clone();
-            }
-        }
-        String::from("Employee") // Default position if not found
-    });
+#[derive(CandidType, Deserialize, Clone)]
+pub struct EmploymentSummary {
+    pub company_username: String,
+    pub company_name: String,
+    pub employee_id: String,
+    pub employee_name: String,
+    pub position: String, // positions held now (or when the employment ended), comma separated
+    pub positions: Vec<EmploymentPosition>,
+    pub unit: Option<String>,
+    pub attributes: EmploymentAttributes,
+    pub period: EmploymentPeriod,
+}
 
-    let random_code = generate_random_code(PROOF_LENTGH as usize).await;
-    let now = ic_cdk::api::time();
+#[derive(CandidType, Deserialize, Clone)]
+struct Proof {
+    code: String,
+    company_username: String,
+    employee_id: String,
+    position: String,
+    created_at: u64,
+    expires_at: u64,
+    is_used: Option<bool>, // legacy single-use flag, only set on proofs issued before verification budgets
+    max_verifications: Option<u32>,
+    verification_count: Option<u32>,
+    last_verified_at: Option<u64>,
+    revoked_at: Option<u64>,
+    revoked_by: Option<String>, // principal of the employee or company admin who revoked it
+    disclosed_claims: Option<Vec<ProofClaim>>, // None -> every claim (proofs issued before selective disclosure)
+    hash_version: Option<u8>, // None -> unkeyed SHA-256 of the code (proofs issued before PROOF_KEY)
+    code_sha256: Option<Vec<u8>>, // unkeyed digest certified with the status, None on keyed proofs issued before it
+    failed_attempts: Option<u32>,
+    locked_at: Option<u64>,
+    employment_start: Option<u64>, // employment as it stood when the proof was issued
+    employment_end: Option<u64>,
+    employment_seq: Option<u32>, // which employment at the company the proof is for, None -> the first
+    positions: Option<Vec<String>>, // None -> `position` (proofs issued before multiple positions)
+    held_during: Option<DateRange>, // None -> positions held when the proof was issued
+    unit: Option<String>, // unit path when the proof was issued
+    attributes: Option<EmploymentAttributes>, // as recorded when the proof was issued
+}
 
-    let proof_id = NEXT_PROOF_ID.with(|next_id| {
-        let mut id = next_id.borrow_mut();
-        let current_id = *id;
-        *id += 1;
-        current_id
-    });
-    let proof_code=format!("{}{}", random_code,proof_id.to_string()); // clear text-Proof ID
+#[derive(CandidType, Deserialize, Clone)]
+pub struct VerificationReceipt {
+    pub verifier: String, // verifier principal, or "anonymous"
+    pub verified_at: u64,
+    pub purpose: Option<String>,
+}
 
-    let mut hasher = Sha256::new();
-    hasher.update(proof_code.as_bytes());
-    let result = hasher.finalize();
-    let hashed_code = hex::encode(result);
+// Claims an employee can choose to reveal; the company username is always part of the proof
+#[derive(CandidType, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProofClaim {
+    CompanyName,
+    EmployeeId,
+    EmployeeName,
+    Position,
+    CreatedAt,
+    EmploymentPeriod,
+    Unit,
+    EmploymentType,
+    WorkLocation,
+    Seniority,
+    WeeklyHours,
+}
 
-    let cur_proof=Proof {
-        code: hashed_code,
-        company_username: company_username.clone(),
-        employee_id: user_id.clone(),
-        position: position,
-        created_at: now,
-        expires_at: now + (24 * 60 * 60 * 1_000_000_000),
-        is_used: false,
+#[derive(CandidType, Deserialize, Clone)]
+pub enum Disclosed<T> {
+    Disclosed(T),
+    Undisclosed,
+}
+
+impl<T> Disclosed<T> {
+    fn when(disclosed: bool, value: T) -> Self {
+        if disclosed {
+            Disclosed::Disclosed(value)
+        } else {
+            Disclosed::Undisclosed
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct ProofResult {
+    pub company_username: String,
+    pub company_name: Disclosed<String>,
+    pub employee_id: Disclosed<String>,
+    pub employee_name: Disclosed<String>,
+    pub position: Disclosed<String>, // attested positions, comma separated
+    pub positions: Disclosed<Vec<String>>,
+    pub held_during: Disclosed<Option<DateRange>>, // period the positions were held throughout, None -> at issuance
+    pub created_at: Disclosed<u64>,
+    pub employment: Disclosed<EmploymentPeriod>,
+    pub unit: Disclosed<Option<String>>, // None -> not assigned to a unit
+    pub employment_type: Disclosed<Option<EmploymentType>>, // None -> not recorded
+    pub work_location: Disclosed<Option<WorkLocation>>,
+    pub seniority: Disclosed<Option<SeniorityLevel>>,
+    pub weekly_hours: Disclosed<Option<u8>>,
+    pub expires_at: u64,
+    pub remaining_verifications: u32,
+}
+
+#[derive(CandidType, Deserialize, Clone, Default)]
+pub struct ProofOptions {
+    pub validity_secs: Option<u64>,
+    pub max_verifications: Option<u32>, // defaults to a single-use proof
+    pub disclose: Option<Vec<ProofClaim>>, // defaults to every claim
+    pub attest: Option<bool>, // also return a signed attestation that can be verified offline
+    pub positions: Option<Vec<String>>, // positions to attest, defaults to every position held
+    pub held_during: Option<DateRange>, // attest positions held throughout this past period instead
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct GeneratedProof {
+    pub proof_code: String,
+    pub proof_id: u128,
+    pub expires_at: u64,
+    pub attestation: Option<SignedAttestation>,
+}
+
+// `payload` is a JSON document; `signature` covers SHA-256 of its UTF-8 bytes
+#[derive(CandidType, Deserialize, Clone)]
+pub struct SignedAttestation {
+    pub payload: String,
+    pub signature: Vec<u8>,
+    pub algorithm: String,
+    pub key_name: String,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct AttestationPublicKey {
+    pub public_key: Vec<u8>, // SEC1 compressed secp256k1 key
+    pub algorithm: String,
+    pub key_name: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialStatus {
+    Current,
+    PositionChanged, // still employed, under a different position than the credential states
+    EmploymentEnded,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct CredentialCheck {
+    pub status: CredentialStatus,
+    pub employee_id: String,
+    pub company_username: String,
+    pub position: String, // as stated in the credential
+    pub issuance_date: String,
+}
+
+// Claims signed into an attestation; undisclosed claims are left out, times are Unix seconds
+#[derive(Serialize)]
+struct AttestationClaims {
+    issuer: String,
+    proof_id: String,
+    company_username: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    company_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    employee_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    employee_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position_held_from: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position_held_until: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issued_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    employment_type: Option<EmploymentType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    work_country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    work_city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seniority: Option<SeniorityLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weekly_hours: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    currently_employed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    employed_from: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    employed_until: Option<u64>,
+    expires_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProofStatus {
+    Active,
+    Used,
+    Expired,
+    Revoked,
+    Locked,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct ProofCheck {
+    pub status: ProofStatus,
+    pub expires_at: u64,
+    pub remaining_verifications: u32,
+}
+
+// Public proof state certified under /proofs/<proof_id>; expiry and lockout are judged against the certificate time
+#[derive(CandidType, Deserialize, Clone)]
+pub struct CertifiedProofStatus {
+    pub proof_id: u128,
+    pub expires_at: u64,
+    pub remaining_verifications: u32,
+    pub revoked: bool,
+    pub locked_until: Option<u64>,
+    pub code_sha256: Option<Vec<u8>>, // binds the status to the proof code
+}
+
+// Public company data certified under /companies/<username>
+#[derive(CandidType, Deserialize, Clone)]
+pub struct CertifiedCompany {
+    pub username: String,
+    pub name: String,
+    pub created_at: u64,
+    pub is_active: bool,
+}
+
+// `value` holds the candid bytes exactly as stored in the leaf, `witness` is the CBOR hash tree for the
+// leaf's path and `certificate` the subnet's certificate over its root
+#[derive(CandidType, Deserialize, Clone)]
+pub struct Certified {
+    pub value: Vec<u8>,
+    pub certificate: Vec<u8>,
+    pub witness: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct ProofSummary {
+    pub proof_id: u128,
+    pub company_username: String,
+    pub position: String,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub status: ProofStatus,
+    pub disclosed_claims: Vec<ProofClaim>,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct CompanyProofSummary {
+    pub proof_id: u128,
+    pub employee_id: String,
+    pub employee_name: String,
+    pub position: String,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub status: ProofStatus,
+    pub verification_count: u32,
+    pub max_verifications: u32,
+    pub last_verified_at: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Default)]
+pub struct ProofFilter {
+    pub status: Option<ProofStatus>,
+    pub employee_id: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct Page {
+    pub offset: u64,
+    pub limit: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct ProofPage {
+    pub proofs: Vec<ProofSummary>,
+    pub total: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct CompanyProofPage {
+    pub proofs: Vec<CompanyProofSummary>,
+    pub total: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct EmploymentClaimPage {
+    pub claims: Vec<EmploymentClaimSummary>,
+    pub total: u64,
+}
+
+impl EmploymentClaim {
+    fn expires_at(&self) -> u64 {
+        self.expires_at.unwrap_or(self.submitted_at + CLAIM_VALIDITY_SECS * 1_000_000_000)
+    }
+}
+
+impl EmploymentPosition {
+    // A position with an unknown start date is never held throughout a range
+    fn held_throughout(&self, range: &DateRange) -> bool {
+        self.start_date.is_some_and(|start| start <= range.from) && self.end_date.is_none_or(|end| end >= range.to)
+    }
+}
+
+impl Employment {
+    // Positions held now (or when the employment ended); those that ended earlier are in POSITION_HISTORY
+    fn positions(&self) -> Vec<EmploymentPosition> {
+        self.positions.clone().unwrap_or_else(|| vec![EmploymentPosition {
+            title: self.position.clone(),
+            start_date: self.start_date,
+            end_date: self.end_date,
+            added_by: None,
+            ended_by: self.ended_by.clone(),
+        }])
+    }
+
+    // Positions held now, or when the employment ended (those end together with it)
+    fn held_positions(&self) -> Vec<String> {
+        self.positions().into_iter()
+            .filter(|position| position.end_date == self.end_date)
+            .map(|position| position.title)
+            .collect()
+    }
+
+    fn period(&self) -> EmploymentPeriod {
+        EmploymentPeriod {
+            status: if self.end_date.is_some() { EmploymentStatus::Former } else { EmploymentStatus::Current },
+            start_date: self.start_date,
+            end_date: self.end_date,
+        }
+    }
+}
+
+impl Proof {
+    fn status(&self, now: u64) -> ProofStatus {
+        if self.revoked_at.is_some() {
+            ProofStatus::Revoked
+        } else if self.is_locked(now) {
+            ProofStatus::Locked
+        } else if self.remaining_verifications() == 0 {
+            ProofStatus::Used
+        } else if self.expires_at < now {
+            ProofStatus::Expired
+        } else {
+            ProofStatus::Active
+        }
+    }
+
+    // Proofs hashed without PROOF_KEY already store the unkeyed digest
+    fn code_sha256(&self) -> Option<Vec<u8>> {
+        match self.hash_version {
+            None => hex::decode(&self.code).ok(),
+            Some(_) => self.code_sha256.clone(),
+        }
+    }
+
+    fn locked_until(&self) -> Option<u64> {
+        self.locked_at.map(|locked_at| locked_at.saturating_add(PROOF_LOCKOUT_SECS * 1_000_000_000))
+    }
+
+    // Locks lift on their own, so a flood of wrong codes can only delay verification
+    fn is_locked(&self, now: u64) -> bool {
+        self.locked_until().is_some_and(|locked_until| now < locked_until)
+    }
+
+    // When the proof stopped being verifiable for good, if it did; a lock is only temporary
+    fn settled_at(&self) -> u64 {
+        if let Some(revoked_at) = self.revoked_at {
+            revoked_at.min(self.expires_at)
+        } else if self.remaining_verifications() == 0 {
+            self.last_verified_at.unwrap_or(self.created_at).min(self.expires_at)
+        } else {
+            self.expires_at
+        }
+    }
+
+    // Compares digests in constant time so mismatches leak nothing about the stored hash
+    fn matches_code(&self, proof_code: &str) -> bool {
+        let Ok(stored_digest) = hex::decode(&self.code) else {
+            return false;
+        };
+        let digest = proof_code_digest(proof_code, self.hash_version);
+        digest.ct_eq(&stored_digest).into()
+    }
+
+    fn disclosed_claims(&self) -> Vec<ProofClaim> {
+        match &self.disclosed_claims {
+            Some(claims) => claims.clone(),
+            None => vec![
+                ProofClaim::CompanyName,
+                ProofClaim::EmployeeId,
+                ProofClaim::EmployeeName,
+                ProofClaim::Position,
+                ProofClaim::CreatedAt,
+                ProofClaim::EmploymentPeriod,
+                ProofClaim::Unit,
+                ProofClaim::EmploymentType,
+                ProofClaim::WorkLocation,
+                ProofClaim::Seniority,
+                ProofClaim::WeeklyHours,
+            ],
+        }
+    }
+
+    // The employment record the proof was issued for wins over the snapshot, so a proof never outlasts
+    // a removal as "current", and a later re-hire does not pass for the attested employment
+    fn employment_period(&self) -> EmploymentPeriod {
+        let employment_seq = self.employment_seq.unwrap_or(0);
+        let employment = if employment_seq == employment_sequence(&self.company_username, &self.employee_id) {
+            get_employment(&self.company_username, &self.employee_id)
+        } else {
+            get_past_employment(&self.company_username, &self.employee_id, employment_seq)
+        };
+        match employment {
+            Some(employment) => employment.period(),
+            None => EmploymentPeriod {
+                status: EmploymentStatus::Former, // the company or the record is gone
+                start_date: self.employment_start,
+                end_date: self.employment_end,
+            },
+        }
+    }
+
+    fn positions(&self) -> Vec<String> {
+        self.positions.clone().unwrap_or_else(|| vec![self.position.clone()])
+    }
+
+    fn discloses(&self, claim: ProofClaim) -> bool {
+        self.disclosed_claims.as_ref().is_none_or(|claims| claims.contains(&claim))
+    }
+
+    fn attributes(&self) -> EmploymentAttributes {
+        self.attributes.clone().unwrap_or_default()
+    }
+
+    fn max_verifications(&self) -> u32 {
+        self.max_verifications.unwrap_or(1)
+    }
+
+    fn verification_count(&self) -> u32 {
+        // Legacy proofs only know whether their single verification was consumed
+        self.verification_count
+            .unwrap_or(if self.is_used == Some(true) { 1 } else { 0 })
+    }
+
+    fn remaining_verifications(&self) -> u32 {
+        self.max_verifications().saturating_sub(self.verification_count())
+    }
+}
+
+// CSPRNG freshly seeded from the management canister; fails rather than falling back to guessable seeds
+async fn seeded_rng() -> Result<ChaCha20Rng, &'static str> {
+    let (random_bytes,) = ic_cdk::api::management_canister::main::raw_rand()
+        .await
+        .map_err(|_| "Randomness is currently unavailable, please try again")?;
+    let seed: [u8; 32] = random_bytes
+        .try_into()
+        .map_err(|_| "Randomness is currently unavailable, please try again")?;
+
+    Ok(ChaCha20Rng::from_seed(seed))
+}
+
+fn is_company_admin(admin_principal: &str, company_username: &str) -> bool {
+    let admin_key = StorableString { value: admin_principal.to_string() };
+
+    EMPLOYEE_COMPANIES_ADMIN.with(|comp| {
+        let map = comp.borrow();
+        if let Some(comp_list) = map.get(&admin_key) {
+            return comp_list.ids.iter().any(|id| id == company_username);
+        }
+        false
+    })
+}
+
+// Current positions, comma separated; None if not currently employed
+fn get_employee_position(company_username: &str, employee_id: &str) -> Option<String> {
+    get_employment(company_username, employee_id)
+        .filter(|employment| employment.end_date.is_none())
+        .map(|employment| employment.held_positions().join(", "))
+}
+
+fn get_employment(company_username: &str, employee_id: &str) -> Option<Employment> {
+    let key = (StorableString { value: company_username.to_string() }, StorableString { value: employee_id.to_string() });
+    EMPLOYMENTS.with(|map| map.borrow().get(&key))
+}
+
+fn get_past_employment(company_username: &str, employee_id: &str, employment_seq: u32) -> Option<Employment> {
+    let key = (StorableString { value: company_username.to_string() }, StorableString { value: employee_id.to_string() });
+    PAST_EMPLOYMENTS.with(|map| map.borrow().get(&(key, employment_seq)))
+}
+
+// Employments at the company that ended before the employee was hired again, oldest first
+fn past_employments(company_username: &str, employee_id: &str) -> Vec<Employment> {
+    let key = (StorableString { value: company_username.to_string() }, StorableString { value: employee_id.to_string() });
+
+    PAST_EMPLOYMENTS.with(|map| {
+        map.borrow()
+            .range((key.clone(), 0)..=(key, u32::MAX))
+            .map(|(_, employment)| employment)
+            .collect()
+    })
+}
+
+// Sequence number of the record in EMPLOYMENTS; the past ones are numbered from 0
+fn employment_sequence(company_username: &str, employee_id: &str) -> u32 {
+    past_employments(company_username, employee_id).len() as u32
+}
+
+// Every position of the employment, including the ones moved to POSITION_HISTORY
+fn position_timeline(company_username: &str, employee_id: &str, employment_seq: u32, employment: &Employment) -> Vec<EmploymentPosition> {
+    let key = (StorableString { value: company_username.to_string() }, StorableString { value: employee_id.to_string() });
+
+    let mut timeline = POSITION_HISTORY.with(|map| {
+        map.borrow()
+            .range((key.clone(), (employment_seq, 0))..=(key, (employment_seq, u32::MAX)))
+            .map(|(_, position)| position)
+            .collect::<Vec<EmploymentPosition>>()
+    });
+    timeline.extend(employment.positions());
+    timeline
+}
+
+// Writes the record in EMPLOYMENTS. Positions that ended while the employment goes on move to
+// POSITION_HISTORY, so however often they change, the record only grows with the positions held at once.
+fn put_employment(company_username: &str, employee_id: &str, mut employment: Employment) {
+    let comp_key = StorableString { value: company_username.to_string() };
+    let emp_key = StorableString { value: employee_id.to_string() };
+
+    if let Some(positions) = employment.positions.as_mut().filter(|_| employment.end_date.is_none()) {
+        let (held, ended): (Vec<EmploymentPosition>, Vec<EmploymentPosition>) = positions.drain(..)
+            .partition(|position| position.end_date.is_none());
+        *positions = held;
+
+        let key = (comp_key.clone(), emp_key.clone());
+        let employment_seq = employment_sequence(company_username, employee_id);
+        POSITION_HISTORY.with(|map| {
+            let mut map = map.borrow_mut();
+            let first = map.range((key.clone(), (employment_seq, 0))..=(key.clone(), (employment_seq, u32::MAX))).count() as u32;
+            for (nth, position) in (first..).zip(ended) {
+                map.insert((key.clone(), (employment_seq, nth)), position);
+            }
+        });
+    }
+
+    EMPLOYMENTS.with(|map| map.borrow_mut().insert((comp_key.clone(), emp_key.clone()), employment));
+    EMPLOYEE_EMPLOYMENTS.with(|idx| idx.borrow_mut().insert((emp_key, comp_key), ()));
+}
+
+fn employment_summary(company_username: String, employee_id: String, employment_seq: u32, employment: &Employment) -> EmploymentSummary {
+    EmploymentSummary {
+        positions: position_timeline(&company_username, &employee_id, employment_seq, employment),
+        company_name: get_company_name_by_id(&company_username),
+        employee_name: get_employee_name_by_id(&employee_id),
+        unit: employment.unit_id.and_then(|unit_id| unit_path(&company_username, unit_id)),
+        attributes: employment.attributes.clone().unwrap_or_default(),
+        company_username,
+        employee_id,
+        position: employment.held_positions().join(", "),
+        period: employment.period(),
+    }
+}
+
+fn is_works_on(user_id: &str, company_username: &str) -> bool {
+    let user_key = StorableString { value: user_id.to_string() };
+
+    EMPLOYEE_COMPANIES.with(|comp| {
+        let map = comp.borrow();
+        if let Some(comp_list) = map.get(&user_key) {
+            return comp_list.ids.iter().any(|id| id == company_username);
+        }
+        false
+    })
+}
+
+#[ic_cdk::update]
+fn set_full_name(full_name: String) -> Result<(), &'static str> {
+    // Validate input
+    if full_name.trim().is_empty() {
+        return Err("Full name cannot be empty");
+    }
+
+    let caller_principal = ic_cdk::caller();
+    let user_id = caller_principal.to_text();
+    let key = StorableString { value: user_id.clone() };
+
+    EMPLOYEE_MAP.with(|emp_map| {
+        let mut map = emp_map.borrow_mut();
+        let employee = if let Some(mut existing) = map.get(&key) {
+            existing.full_name = full_name.clone();
+            existing
+        } else {
+            Employee { id: user_id.clone(), full_name: full_name.clone() }
+        };
+        map.insert(key, employee);
+    });
+
+    Ok(())
+}
+
+fn get_employee_name_by_id(emp_id: &str) -> String {
+    let key = StorableString { value: emp_id.to_string() };
+    EMPLOYEE_MAP.with(|emp_map| {
+        let map_ref = emp_map.borrow();
+        if let Some(employee) = map_ref.get(&key) {
+            employee.full_name.clone()
+        } else {
+            emp_id.to_string()
+        }
+    })
+}
+
+#[ic_cdk::query]
+fn get_my_name() -> Result<String, &'static str> {
+    let caller_principal = ic_cdk::caller();
+    let user_id = caller_principal.to_text();
+    let key = StorableString { value: user_id };
+
+    EMPLOYEE_MAP.with(|emp_map| {
+        let map_ref = emp_map.borrow();
+        if let Some(employee) = map_ref.get(&key) {
+            if employee.full_name.trim().is_empty() {
+                Err("Name not set")
+            } else {
+                Ok(employee.full_name.clone())
+            }
+        } else {
+            Err("Name not set")
+        }
+    })
+}
+
+// Picks how long a new proof stays valid, bounded by the company's maximum (if any)
+fn resolve_proof_validity(company_username: &str, requested_secs: Option<u64>) -> Result<u64, &'static str> {
+    let company_max = COMPANY_MAP.with(|mp| {
+        let comp_key = StorableString { value: company_username.to_string() };
+        mp.borrow().get(&comp_key).and_then(|company| company.max_proof_validity_secs)
+    });
+    let max_secs = company_max.unwrap_or(MAX_PROOF_VALIDITY_SECS).min(MAX_PROOF_VALIDITY_SECS);
+
+    match requested_secs {
+        Some(0) => Err("Proof validity must be greater than zero"),
+        Some(secs) if secs > max_secs => Err("Requested validity exceeds the allowed maximum"),
+        Some(secs) => Ok(secs),
+        None => Ok(DEFAULT_PROOF_VALIDITY_SECS.min(max_secs)),
+    }
+}
+
+fn get_company_name_by_id(comp_username: &str) -> String {
+    let key = StorableString { value: comp_username.to_string() };
+    COMPANY_MAP.with(|comp_map| {
+        let map_ref = comp_map.borrow();
+        if let Some(company) = map_ref.get(&key) {
+            company.name.clone()
+        } else {
+            comp_username.to_string()
+        }
+    })
+}
+
+fn attestation_key_name() -> String {
+    CONFIG.with(|c| c.borrow().get().attestation_key_name.clone())
+        .unwrap_or_else(|| attestation::DEFAULT_KEY_NAME.to_string())
+}
+
+async fn sign_attestation(proof_id: u128, proof: &Proof) -> Result<SignedAttestation, &'static str> {
+    let employment = proof.discloses(ProofClaim::EmploymentPeriod).then(|| proof.employment_period());
+    let held_during = proof.held_during.filter(|_| proof.discloses(ProofClaim::Position));
+    let attributes = proof.attributes();
+    let location = attributes.location.filter(|_| proof.discloses(ProofClaim::WorkLocation));
+    let claims = AttestationClaims {
+        issuer: ic_cdk::id().to_text(),
+        proof_id: proof_id.to_string(),
+        company_username: proof.company_username.clone(),
+        company_name: proof.discloses(ProofClaim::CompanyName)
+            .then(|| get_company_name_by_id(&proof.company_username)),
+        employee_id: proof.discloses(ProofClaim::EmployeeId).then(|| proof.employee_id.clone()),
+        employee_name: proof.discloses(ProofClaim::EmployeeName)
+            .then(|| get_employee_name_by_id(&proof.employee_id)),
+        position: proof.discloses(ProofClaim::Position).then(|| proof.position.clone()),
+        position_held_from: held_during.map(|range| range.from / 1_000_000_000),
+        position_held_until: held_during.map(|range| range.to / 1_000_000_000),
+        issued_at: proof.discloses(ProofClaim::CreatedAt).then_some(proof.created_at / 1_000_000_000),
+        unit: proof.unit.clone().filter(|_| proof.discloses(ProofClaim::Unit)),
+        employment_type: attributes.employment_type.filter(|_| proof.discloses(ProofClaim::EmploymentType)),
+        work_country: location.as_ref().map(|location| location.country.clone()),
+        work_city: location.and_then(|location| location.city),
+        seniority: attributes.seniority.filter(|_| proof.discloses(ProofClaim::Seniority)),
+        weekly_hours: attributes.weekly_hours.filter(|_| proof.discloses(ProofClaim::WeeklyHours)),
+        currently_employed: employment.as_ref().map(|period| period.status == EmploymentStatus::Current),
+        employed_from: employment.as_ref().and_then(|period| period.start_date).map(|date| date / 1_000_000_000),
+        employed_until: employment.as_ref().and_then(|period| period.end_date).map(|date| date / 1_000_000_000),
+        expires_at: proof.expires_at / 1_000_000_000,
+    };
+    let payload = serde_json::to_string(&claims).map_err(|_| "Failed to encode the attestation")?;
+
+    let key_name = attestation_key_name();
+    let signature = attestation::sign(&key_name, payload.as_bytes()).await?;
+
+    Ok(SignedAttestation {
+        payload,
+        signature,
+        algorithm: attestation::ALGORITHM.to_string(),
+        key_name,
+    })
+}
+
+#[ic_cdk::update]
+async fn get_attestation_public_key() -> Result<AttestationPublicKey, &'static str> {
+    let key_name = attestation_key_name();
+    let public_key = attestation::public_key(&key_name).await?;
+
+    Ok(AttestationPublicKey {
+        public_key,
+        algorithm: attestation::ALGORITHM.to_string(),
+        key_name,
+    })
+}
+
+// Returns the caller's employment at the company as a signed W3C Verifiable Credential (JSON)
+#[ic_cdk::update]
+async fn export_employment_credential(comp_username: String) -> Result<String, &'static str> {
+    let user_id = ic_cdk::caller().to_text();
+
+    let comp_key = StorableString { value: comp_username.clone() };
+    let company = COMPANY_MAP.with(|mp| mp.borrow().get(&comp_key)).ok_or("Company not found")?;
+    if !is_works_on(&user_id, &comp_username) {
+        return Err("Caller is not works in this company");
+    }
+    let position = get_employee_position(&comp_username, &user_id).ok_or("Employee not found in company")?;
+
+    let issuer = ic_cdk::id().to_text();
+    let now = ic_cdk::api::time();
+    let mut credential = credential::EmploymentCredential::new(&issuer, now, credential::EmploymentSubject {
+        id: credential::did(&user_id),
+        name: get_employee_name_by_id(&user_id),
+        job_title: position,
+        works_for: credential::Organization {
+            identifier: comp_username,
+            name: company.name,
+        },
+    });
+
+    attestation::take_signing_quota(&user_id, now)?;
+    let key_name = attestation_key_name();
+    let signature = attestation::sign(&key_name, &credential.signing_input()).await?;
+    credential.proof = Some(credential::CredentialProof {
+        proof_type: credential::PROOF_TYPE.to_string(),
+        created: credential.issuance_date.clone(),
+        proof_purpose: credential::PROOF_PURPOSE.to_string(),
+        verification_method: format!("{}#{}", credential::did(&issuer), key_name),
+        proof_value: hex::encode(signature),
+    });
+
+    serde_json::to_string(&credential).map_err(|_| "Failed to encode the credential")
+}
+
+// Checks the signature of a credential issued by this canister, then compares it with the current employment
+#[ic_cdk::update]
+async fn verify_employment_credential(credential_json: String) -> Result<CredentialCheck, &'static str> {
+    let credential: credential::EmploymentCredential = serde_json::from_str(&credential_json)
+        .map_err(|_| "Malformed credential")?;
+    let proof = credential.proof.as_ref().ok_or("Credential is not signed")?;
+
+    let issuer = credential::did(&ic_cdk::id().to_text());
+    if credential.issuer != issuer {
+        return Err("Credential was not issued by this canister");
+    }
+    if proof.proof_type != credential::PROOF_TYPE || proof.proof_purpose != credential::PROOF_PURPOSE {
+        return Err("Unsupported credential proof");
+    }
+    let key_name = attestation_key_name();
+    if proof.verification_method != format!("{}#{}", issuer, key_name) {
+        return Err("Credential was signed with an unknown key");
+    }
+
+    let signature = hex::decode(&proof.proof_value).map_err(|_| "Malformed credential")?;
+    let public_key = attestation::public_key(&key_name).await?;
+    if !credential::verify_signature(&public_key, &credential.signing_input(), &signature) {
+        return Err("Credential signature is invalid");
+    }
+
+    let subject = credential.credential_subject;
+    let employee_id = subject.id.strip_prefix(credential::DID_PREFIX).ok_or("Malformed credential")?.to_string();
+    let company_username = subject.works_for.identifier;
+    let status = match get_employee_position(&company_username, &employee_id) {
+        Some(position) if position == subject.job_title => CredentialStatus::Current,
+        Some(_) => CredentialStatus::PositionChanged,
+        None => CredentialStatus::EmploymentEnded,
+    };
+
+    Ok(CredentialCheck {
+        status,
+        employee_id,
+        company_username,
+        position: subject.job_title,
+        issuance_date: credential.issuance_date,
+    })
+}
+
+#[ic_cdk::update]
+async fn generate_proof(company_username:String, options: Option<ProofOptions>) -> Result<GeneratedProof, &'static str> {
+    let caller_principal = ic_cdk::caller();
+    let user_id: String = caller_principal.to_text();
+    let options = options.unwrap_or_default();
+
+    // Former employees can still prove the employment they had
+    let employment = get_employment(&company_username, &user_id).ok_or("Caller is not works in this company")?;
+
+    let validity_secs = resolve_proof_validity(&company_username, options.validity_secs)?;
+    let max_verifications = options.max_verifications.unwrap_or(1);
+    if max_verifications == 0 || max_verifications > MAX_PROOF_VERIFICATIONS {
+        return Err("Verification count must be between 1 and 100");
+    }
+    let disclosed_claims = options.disclose.map(|mut claims| {
+        claims.sort();
+        claims.dedup();
+        claims
+    });
+
+    // Attest the chosen positions, or every position held (at the end, for former employees,
+    // or throughout the requested period)
+    let held_positions = match options.held_during {
+        None => employment.held_positions(),
+        Some(range) => {
+            if range.from > range.to || range.to > ic_cdk::api::time() {
+                return Err("Invalid period");
+            }
+            let employment_seq = employment_sequence(&company_username, &user_id);
+            let mut titles = position_timeline(&company_username, &user_id, employment_seq, &employment).into_iter()
+                .filter(|position| position.held_throughout(&range))
+                .map(|position| position.title)
+                .collect::<Vec<String>>();
+            titles.sort();
+            titles.dedup();
+            if titles.is_empty() {
+                return Err("No position was held throughout the given period");
+            }
+            titles
+        }
+    };
+    let positions = match options.positions {
+        None => held_positions,
+        Some(mut requested) => {
+            requested.sort();
+            requested.dedup();
+            if requested.is_empty() {
+                return Err("Select at least one position");
+            }
+            if requested.iter().any(|position| !held_positions.contains(position)) {
+                return Err("Position not held by the employee");
+            }
+            requested
+        }
+    };
+    let position_bytes = positions.iter().map(|position| position.len()).sum::<usize>();
+    if positions.len() > MAX_ATTESTED_POSITIONS || position_bytes > MAX_ATTESTED_POSITION_BYTES {
+        return Err("Too many positions for one proof, select fewer positions");
+    }
+
+    if options.attest.unwrap_or(false) {
+        attestation::take_signing_quota(&user_id, ic_cdk::api::time())?;
+    }
+
+    ensure_proof_key().await?;
+    let mut rng = seeded_rng().await?;
+    let mut secret = vec![0u8; PROOF_SECRET_BYTES];
+    rng.fill_bytes(&mut secret);
+    let now = ic_cdk::api::time();
+
+    let proof_id = NEXT_PROOF_ID.with(|next_id| {
+        let mut id = next_id.borrow_mut();
+        let current_id = *id.get();
+        id.set(current_id + 1).expect("Failed to persist the proof ID counter");
+        current_id
+    });
+    let proof_code = token::encode(&token::ProofToken {
+        canister_id: ic_cdk::id().as_slice().to_vec(),
+        proof_id,
+        secret,
+    });
+
+    let hashed_code = hash_proof_code(&proof_code, Some(PROOF_HASH_VERSION));
+
+    let cur_proof=Proof {
+        code: hashed_code,
+        company_username: company_username.clone(),
+        employee_id: user_id.clone(),
+        position: positions.join(", "),
+        positions: Some(positions),
+        held_during: options.held_during,
+        unit: employment.unit_id.and_then(|unit_id| unit_path(&company_username, unit_id)),
+        attributes: employment.attributes.clone(),
+        created_at: now,
+        expires_at: now + validity_secs * 1_000_000_000,
+        is_used: None,
+        max_verifications: Some(max_verifications),
+        verification_count: Some(0),
+        last_verified_at: None,
+        revoked_at: None,
+        revoked_by: None,
+        disclosed_claims,
+        hash_version: Some(PROOF_HASH_VERSION),
+        code_sha256: Some(proof_code_digest(&proof_code, None)),
+        failed_attempts: Some(0),
+        locked_at: None,
+        employment_start: employment.start_date,
+        employment_end: employment.end_date,
+        employment_seq: Some(employment_sequence(&company_username, &user_id)),
+    };
+
+    // The limits above keep proofs within bounds; never let an oversized one reach the map and trap
+    if Encode!(&cur_proof).map_or(true, |bytes| bytes.len() > <Proof as BoundedStorable>::MAX_SIZE as usize) {
+        return Err("Proof is too large");
+    }
+
+    // Sign before storing, so a failed signature leaves no proof behind
+    let attestation = if options.attest.unwrap_or(false) {
+        Some(sign_attestation(proof_id, &cur_proof).await?)
+    } else {
+        None
+    };
+
+    certify_proof(proof_id, Some(&cur_proof));
+    PROOF_MAP.with(|p|{
+        p.borrow_mut().insert(proof_id, cur_proof.clone());
+    });
+    EMPLOYEE_PROOFS.with(|idx| {
+        idx.borrow_mut().insert((StorableString { value: user_id.clone() }, proof_id), ());
+    });
+    COMPANY_PROOFS.with(|idx| {
+        idx.borrow_mut().insert((StorableString { value: company_username.clone() }, proof_id), ());
+    });
+    Ok(GeneratedProof {
+        proof_code,
+        proof_id,
+        expires_at: cur_proof.expires_at,
+        attestation,
+    })
+}
+
+#[ic_cdk::query]
+fn list_my_companies() -> Vec<String> {
+    let caller_principal = ic_cdk::caller();
+
+    let user_id = StorableString {
+        value : caller_principal.to_text(),
+    };
+    
+    EMPLOYEE_COMPANIES.with(|map| {
+        let map_ref = map.borrow();
+        match map_ref.get(&user_id) {
+            Some(id_list) => id_list.ids.clone(),
+            None => Vec::new(),
+        }
+    })
+}
+
+#[ic_cdk::query]
+fn list_my_admin_companies() -> Vec<String> {
+    let caller_principal = ic_cdk::caller();
+
+    let user_id = StorableString {
+        value : caller_principal.to_text(),
+    };
+    
+    EMPLOYEE_COMPANIES_ADMIN.with(|map| {
+        let map_ref = map.borrow();
+        match map_ref.get(&user_id) {
+            Some(id_list) => id_list.ids.clone(),
+            None => Vec::new(),
+        }
+    })
+}
+
+#[ic_cdk::query]
+fn get_company_name(comp_username: String) -> Result<String, &'static str> {
+    let storable_comp_username = StorableString { value: comp_username };
+    
+    COMPANY_MAP.with(|map| {
+        let map_ref = map.borrow();
+        match map_ref.get(&storable_comp_username) {
+            Some(company) => Ok(company.name.clone()),
+            None => Err("Company not found"),
+        }
+    })
+}
+
+
+#[ic_cdk::query]
+fn list_company_employess(comp_username:String, unit_id: Option<u64>) -> Result<Vec<CompanyEmployeeWithName>, &'static str> {
+    let caller_principal = ic_cdk::caller();
+    
+    // Company admins see everyone, unit admins the employees of a unit they manage
+    let may_view = match unit_id {
+        None => is_company_admin(&caller_principal.to_text(), &comp_username),
+        Some(unit_id) => can_manage_unit(&caller_principal.to_text(), &comp_username, unit_id),
+    };
+    if !may_view {
+        return Err("Only company admin can view employee list");
+    }
+    // The unit and every unit below it
+    let units = unit_id.map(|unit_id| unit_subtree(&comp_username, unit_id));
+    
+    // The roster is every employment record that has not ended
+    let employees = company_employments(&comp_username).into_iter()
+        .filter(|(_, employment)| employment.end_date.is_none())
+        .filter(|(_, employment)| {
+            units.as_ref().is_none_or(|units| employment.unit_id.is_some_and(|unit| units.contains(&unit)))
+        })
+        .map(|(employee_id, employment)| {
+            let positions = employment.held_positions();
+            CompanyEmployeeWithName {
+                employee_name: get_employee_name_by_id(&employee_id),
+                employee_id,
+                position: positions.join(", "),
+                positions,
+                unit: employment.unit_id.and_then(|unit| unit_path(&comp_username, unit)),
+                attributes: employment.attributes.unwrap_or_default(),
+            }
+        })
+        .collect::<Vec<CompanyEmployeeWithName>>();
+    Ok(employees)
+}
+
+#[ic_cdk::update]
+fn add_employee(comp_username:String, emp_id:String, position:String, start_date: Option<u64>) -> Result<(), &'static str> {
+    let caller_principal = ic_cdk::caller();
+
+    if !is_company_admin(&caller_principal.to_text(), &comp_username) {
+        return Err("Only company admin can add employees");
+    }
+
+    // Validate inputs
+    if emp_id.trim().is_empty() {
+        return Err("Employee ID cannot be empty");
+    }
+    if Principal::from_text(&emp_id).is_err() {
+        return Err("Employee ID must be a principal");
+    }
+    if position.trim().is_empty() {
+        return Err("Position cannot be empty");
+    }
+    if position.len() > MAX_POSITION_LENGTH {
+        return Err("Position is too long");
+    }
+    let now = ic_cdk::api::time();
+    if start_date.is_some_and(|date| date > now) {
+        return Err("Start date cannot be in the future");
+    }
+
+    let admin_id = caller_principal.to_text();
+
+    // Current employees already agreed to join; only their position changes
+    if let Some(mut current) = get_employment(&comp_username, &emp_id).filter(|e| e.end_date.is_none()) {
+        if start_date.is_some() {
+            current.start_date = start_date;
+        }
+        let employment = change_position(current, position, now, &admin_id)?;
+        put_employment(&comp_username, &emp_id, employment);
+        return Ok(());
+    }
+
+    // Everyone else is invited and only joins the company once they accept
+    put_invite(&comp_username, &emp_id, Invite {
+        position,
+        start_date,
+        invited_by: admin_id,
+        created_at: now,
+        expires_at: now + INVITE_VALIDITY_SECS * 1_000_000_000,
+    });
+
+    Ok(())
+}
+
+// Makes an accepted invite or an approved claim a membership; re-joining as a former employee starts a new employment
+fn join_company(comp_username: &str, emp_id: &str, position: String, start_date: Option<u64>, added_by: String) {
+    // Whichever of the two came first, the other one is settled as well
+    take_invite(comp_username, emp_id);
+    take_claim(comp_username, emp_id);
+
+    // Add company to EMPLOYEE_COMPANIES (employee -> companies)
+    EMPLOYEE_COMPANIES.with(|emp|{
+        let mut map=emp.borrow_mut();
+        let emp_key = StorableString { value: emp_id.to_string() };
+       
+        if let Some(mut comp_list) = map.get(&emp_key) {
+            if !comp_list.ids.iter().any(|id| id == comp_username) {
+                comp_list.ids.push(comp_username.to_string());
+                map.insert(emp_key, comp_list);
+            }
+        } else {
+            let new_list = IDList { ids: vec![comp_username.to_string()] };
+            map.insert(emp_key, new_list);
+        }
+    });
+
+    // A re-hire starts a new employment; the one that ended stays on record
+    if let Some(former) = get_employment(comp_username, emp_id).filter(|employment| employment.end_date.is_some()) {
+        let key = (StorableString { value: comp_username.to_string() }, StorableString { value: emp_id.to_string() });
+        let employment_seq = employment_sequence(comp_username, emp_id);
+        PAST_EMPLOYMENTS.with(|map| map.borrow_mut().insert((key, employment_seq), former));
+    }
+
+    let start_date = start_date.unwrap_or_else(ic_cdk::api::time);
+    put_employment(comp_username, emp_id, Employment {
+        position: position.clone(),
+        start_date: Some(start_date),
+        end_date: None,
+        ended_by: None,
+        positions: Some(vec![EmploymentPosition {
+            title: position,
+            start_date: Some(start_date),
+            end_date: None,
+            added_by: Some(added_by),
+            ended_by: None,
+        }]),
+        unit_id: None,
+        attributes: None,
+    });
+}
+
+fn put_invite(comp_username: &str, emp_id: &str, invite: Invite) {
+    let comp_key = StorableString { value: comp_username.to_string() };
+    let emp_key = StorableString { value: emp_id.to_string() };
+
+    INVITES.with(|map| map.borrow_mut().insert((comp_key.clone(), emp_key.clone()), invite));
+    EMPLOYEE_INVITES.with(|idx| idx.borrow_mut().insert((emp_key, comp_key), ()));
+}
+
+fn take_invite(comp_username: &str, emp_id: &str) -> Option<Invite> {
+    let comp_key = StorableString { value: comp_username.to_string() };
+    let emp_key = StorableString { value: emp_id.to_string() };
+
+    EMPLOYEE_INVITES.with(|idx| idx.borrow_mut().remove(&(emp_key.clone(), comp_key.clone())));
+    INVITES.with(|map| map.borrow_mut().remove(&(comp_key, emp_key)))
+}
+
+fn invite_summary(comp_username: String, emp_id: String, invite: Invite) -> InviteSummary {
+    InviteSummary {
+        company_name: get_company_name_by_id(&comp_username),
+        employee_name: get_employee_name_by_id(&emp_id),
+        company_username: comp_username,
+        employee_id: emp_id,
+        position: invite.position,
+        start_date: invite.start_date,
+        invited_by: invite.invited_by,
+        created_at: invite.created_at,
+        expires_at: invite.expires_at,
+    }
+}
+
+#[ic_cdk::update]
+fn accept_invite(comp_username: String) -> Result<(), &'static str> {
+    let user_id = ic_cdk::caller().to_text();
+
+    let invite = take_invite(&comp_username, &user_id).ok_or("Invite not found")?;
+    if invite.expires_at < ic_cdk::api::time() {
+        return Err("Invite expired");
+    }
+    let comp_key = StorableString { value: comp_username.clone() };
+    if !COMPANY_MAP.with(|mp| mp.borrow().contains_key(&comp_key)) {
+        return Err("Company not found");
+    }
+
+    join_company(&comp_username, &user_id, invite.position, invite.start_date, invite.invited_by);
+    Ok(())
+}
+
+#[ic_cdk::update]
+fn decline_invite(comp_username: String) -> Result<(), &'static str> {
+    let user_id = ic_cdk::caller().to_text();
+    take_invite(&comp_username, &user_id).map(|_| ()).ok_or("Invite not found")
+}
+
+#[ic_cdk::update]
+fn cancel_invite(comp_username: String, emp_id: String) -> Result<(), &'static str> {
+    if !is_company_admin(&ic_cdk::caller().to_text(), &comp_username) {
+        return Err("Only company admin can cancel invites");
+    }
+    take_invite(&comp_username, &emp_id).map(|_| ()).ok_or("Invite not found")
+}
+
+#[ic_cdk::query]
+fn list_my_invites() -> Vec<InviteSummary> {
+    let user_id = ic_cdk::caller().to_text();
+    let emp_key = StorableString { value: user_id.clone() };
+    let now = ic_cdk::api::time();
+
+    let comp_ids = EMPLOYEE_INVITES.with(|idx| {
+        idx.borrow()
+            .range((emp_key.clone(), StorableString::default())..)
+            .take_while(|((emp, _), _)| *emp == emp_key)
+            .map(|((_, comp), _)| comp)
+            .collect::<Vec<StorableString>>()
+    });
+    comp_ids.into_iter()
+        .filter_map(|comp_key| {
+            let invite = INVITES.with(|map| map.borrow().get(&(comp_key.clone(), emp_key.clone())))?;
+            Some(invite_summary(comp_key.value, user_id.clone(), invite))
+        })
+        .filter(|invite| invite.expires_at >= now)
+        .collect()
+}
+
+#[ic_cdk::query]
+fn list_company_invites(comp_username: String) -> Result<Vec<InviteSummary>, &'static str> {
+    if !is_company_admin(&ic_cdk::caller().to_text(), &comp_username) {
+        return Err("Only company admin can view invites");
+    }
+    let comp_key = StorableString { value: comp_username.clone() };
+    let now = ic_cdk::api::time();
+
+    Ok(INVITES.with(|map| {
+        map.borrow()
+            .range((comp_key.clone(), StorableString::default())..)
+            .take_while(|((comp, _), _)| *comp == comp_key)
+            .filter(|(_, invite)| invite.expires_at >= now)
+            .map(|((_, emp), invite)| invite_summary(comp_username.clone(), emp.value, invite))
+            .collect()
+    }))
+}
+
+fn take_claim(comp_username: &str, emp_id: &str) -> Option<EmploymentClaim> {
+    let comp_key = StorableString { value: comp_username.to_string() };
+    let emp_key = StorableString { value: emp_id.to_string() };
+
+    EMPLOYEE_CLAIMS.with(|idx| idx.borrow_mut().remove(&(emp_key.clone(), comp_key.clone())));
+    EMPLOYMENT_CLAIMS.with(|map| map.borrow_mut().remove(&(comp_key, emp_key)))
+}
+
+fn claim_summary(comp_username: String, emp_id: String, claim: EmploymentClaim) -> EmploymentClaimSummary {
+    EmploymentClaimSummary {
+        company_name: get_company_name_by_id(&comp_username),
+        employee_name: get_employee_name_by_id(&emp_id),
+        company_username: comp_username,
+        employee_id: emp_id,
+        expires_at: claim.expires_at(),
+        position: claim.position,
+        start_date: claim.start_date,
+        submitted_at: claim.submitted_at,
+    }
+}
+
+// Asks the company to confirm the caller's employment; a new claim replaces the pending one
+#[ic_cdk::update]
+fn submit_employment_claim(comp_username: String, position: String, start_date: Option<u64>) -> Result<(), &'static str> {
+    let caller_principal = ic_cdk::caller();
+    if caller_principal == Principal::anonymous() {
+        return Err("Anonymous principals cannot claim employment");
+    }
+    let user_id = caller_principal.to_text();
+
+    if position.trim().is_empty() {
+        return Err("Position cannot be empty");
+    }
+    if position.len() > MAX_POSITION_LENGTH {
+        return Err("Position is too long");
+    }
+    let now = ic_cdk::api::time();
+    if start_date.is_some_and(|date| date > now) {
+        return Err("Start date cannot be in the future");
+    }
+    let comp_key = StorableString { value: comp_username.clone() };
+    if !COMPANY_MAP.with(|mp| mp.borrow().contains_key(&comp_key)) {
+        return Err("Company not found");
+    }
+    if get_employment(&comp_username, &user_id).is_some_and(|e| e.end_date.is_none()) {
+        return Err("Caller already works in this company");
+    }
+
+    let emp_key = StorableString { value: user_id };
+    EMPLOYMENT_CLAIMS.with(|map| {
+        map.borrow_mut().insert((comp_key.clone(), emp_key.clone()), EmploymentClaim {
+            position,
+            start_date,
+            submitted_at: now,
+            expires_at: Some(now + CLAIM_VALIDITY_SECS * 1_000_000_000),
+        })
+    });
+    EMPLOYEE_CLAIMS.with(|idx| idx.borrow_mut().insert((emp_key, comp_key), ()));
+    Ok(())
+}
+
+#[ic_cdk::update]
+fn withdraw_employment_claim(comp_username: String) -> Result<(), &'static str> {
+    let user_id = ic_cdk::caller().to_text();
+    take_claim(&comp_username, &user_id).map(|_| ()).ok_or("Claim not found")
+}
+
+#[ic_cdk::query]
+fn list_my_employment_claims() -> Vec<EmploymentClaimSummary> {
+    let user_id = ic_cdk::caller().to_text();
+    let emp_key = StorableString { value: user_id.clone() };
+    let now = ic_cdk::api::time();
+
+    let comp_ids = EMPLOYEE_CLAIMS.with(|idx| {
+        idx.borrow()
+            .range((emp_key.clone(), StorableString::default())..)
+            .take_while(|((emp, _), _)| *emp == emp_key)
+            .map(|((_, comp), _)| comp)
+            .collect::<Vec<StorableString>>()
+    });
+    comp_ids.into_iter()
+        .filter_map(|comp_key| {
+            let claim = EMPLOYMENT_CLAIMS.with(|map| map.borrow().get(&(comp_key.clone(), emp_key.clone())))?;
+            if claim.expires_at() < now {
+                return None;
+            }
+            Some(claim_summary(comp_key.value, user_id.clone(), claim))
+        })
+        .collect()
+}
+
+// The company's review queue, oldest claim first
+#[ic_cdk::query]
+fn list_company_employment_claims(comp_username: String, page: Option<Page>) -> Result<EmploymentClaimPage, &'static str> {
+    if !is_company_admin(&ic_cdk::caller().to_text(), &comp_username) {
+        return Err("Only company admin can review employment claims");
+    }
+    let comp_key = StorableString { value: comp_username.clone() };
+    let now = ic_cdk::api::time();
+    let (offset, limit) = page_bounds(page);
+
+    let mut queue = EMPLOYMENT_CLAIMS.with(|map| {
+        map.borrow()
+            .range((comp_key.clone(), StorableString::default())..)
+            .take_while(|((comp, _), _)| *comp == comp_key)
+            .filter(|(_, claim)| claim.expires_at() >= now)
+            .map(|((_, emp), claim)| (emp, claim))
+            .collect::<Vec<(StorableString, EmploymentClaim)>>()
+    });
+    queue.sort_by_key(|(_, claim)| claim.submitted_at);
+
+    let claims = queue.iter()
+        .skip(offset)
+        .take(limit)
+        .map(|(emp, claim)| claim_summary(comp_username.clone(), emp.value.clone(), claim.clone()))
+        .collect::<Vec<EmploymentClaimSummary>>();
+
+    Ok(EmploymentClaimPage {
+        claims,
+        total: queue.len() as u64,
+    })
+}
+
+#[ic_cdk::update]
+fn review_employment_claim(comp_username: String, emp_id: String, review: ClaimReview) -> Result<(), &'static str> {
+    let admin_id = ic_cdk::caller().to_text();
+    if !is_company_admin(&admin_id, &comp_username) {
+        return Err("Only company admin can review employment claims");
+    }
+
+    let claim_key = (StorableString { value: comp_username.clone() }, StorableString { value: emp_id.clone() });
+    let claim = EMPLOYMENT_CLAIMS.with(|map| map.borrow().get(&claim_key)).ok_or("Claim not found")?;
+    if claim.expires_at() < ic_cdk::api::time() {
+        return Err("Claim expired");
+    }
+    let (position, start_date) = match review {
+        ClaimReview::Reject => {
+            take_claim(&comp_username, &emp_id);
+            return Ok(());
+        }
+        ClaimReview::Approve => (claim.position, claim.start_date),
+        ClaimReview::Amend { position, start_date } => {
+            let position = position.unwrap_or(claim.position);
+            if position.trim().is_empty() {
+                return Err("Position cannot be empty");
+            }
+            if position.len() > MAX_POSITION_LENGTH {
+                return Err("Position is too long");
+            }
+            if start_date.is_some_and(|date| date > ic_cdk::api::time()) {
+                return Err("Start date cannot be in the future");
+            }
+            (position, start_date.or(claim.start_date))
+        }
     };
+    if get_employment(&comp_username, &emp_id).is_some_and(|e| e.end_date.is_none()) {
+        return Err("Employee already works in this company");
+    }
+
+    join_company(&comp_username, &emp_id, position, start_date, admin_id);
+    Ok(())
+}
+
+// The new position replaces the ones held so far as of `effective_date`; add_employee_position adds one alongside them
+fn change_position(mut employment: Employment, position: String, effective_date: u64, admin_id: &str) -> Result<Employment, &'static str> {
+    let mut positions = employment.positions();
+    if positions.iter().any(|p| p.end_date.is_none() && p.title == position) {
+        return Ok(employment);
+    }
+    if positions.iter().any(|p| p.end_date.is_none() && p.start_date.is_some_and(|start| start > effective_date)) {
+        return Err("Effective date precedes a current position");
+    }
+
+    for held in positions.iter_mut().filter(|p| p.end_date.is_none()) {
+        held.end_date = Some(effective_date);
+        held.ended_by = Some(admin_id.to_string());
+    }
+    positions.push(EmploymentPosition {
+        title: position.clone(),
+        start_date: Some(effective_date),
+        end_date: None,
+        added_by: Some(admin_id.to_string()),
+        ended_by: None,
+    });
+    employment.position = position;
+    employment.positions = Some(positions);
+    Ok(employment)
+}
+
+// Records a position change that took effect on `effective_date` (defaults to now)
+#[ic_cdk::update]
+fn change_employee_position(comp_username: String, emp_id: String, position: String, effective_date: Option<u64>) -> Result<(), &'static str> {
+    let admin_id = ic_cdk::caller().to_text();
+    if !can_manage_employee(&admin_id, &comp_username, &emp_id) {
+        return Err("Only company or unit admin can manage positions");
+    }
+    if position.trim().is_empty() {
+        return Err("Position cannot be empty");
+    }
+    if position.len() > MAX_POSITION_LENGTH {
+        return Err("Position is too long");
+    }
+    let now = ic_cdk::api::time();
+    let effective_date = effective_date.unwrap_or(now);
+    if effective_date > now {
+        return Err("Effective date cannot be in the future");
+    }
+
+    let employment = get_employment(&comp_username, &emp_id)
+        .filter(|employment| employment.end_date.is_none())
+        .ok_or("Employee not found in this company")?;
+    let employment = change_position(employment, position, effective_date, &admin_id)?;
+
+    put_employment(&comp_username, &emp_id, employment);
+    Ok(())
+}
+
+#[ic_cdk::update]
+fn remove_employee(comp_username:String, emp_id:String, revoke_proofs: Option<bool>) -> Result<(), &'static str> {
+    let caller_principal = ic_cdk::caller();
+    if !can_manage_employee(&caller_principal.to_text(), &comp_username, &emp_id) {
+        return Err("Only company or unit admin can remove employees");
+    }
+
+    end_membership(&comp_username, &emp_id, &caller_principal.to_text())?;
+
+    if revoke_proofs.unwrap_or(false) {
+        revoke_employee_proofs(&comp_username, &emp_id, &caller_principal.to_text());
+    }
+
+    Ok(())
+}
+
+// Ends the caller's own employment; the history record stays, their active proofs for the company are revoked
+#[ic_cdk::update]
+fn leave_company(comp_username: String) -> Result<(), &'static str> {
+    let user_id = ic_cdk::caller().to_text();
+    if !is_works_on(&user_id, &comp_username) {
+        return Err("Caller is not works in this company");
+    }
+
+    end_membership(&comp_username, &user_id, &user_id)?;
+    revoke_employee_proofs(&comp_username, &user_id, &user_id);
+    notify_company(&comp_username, NotificationKind::EmployeeLeft, &user_id);
+    Ok(())
+}
+
+fn notify_company(comp_username: &str, kind: NotificationKind, employee_id: &str) {
+    let notification_id = NEXT_NOTIFICATION_ID.with(|next_id| {
+        let mut id = next_id.borrow_mut();
+        let current_id = *id.get();
+        id.set(current_id + 1).expect("Failed to persist the notification ID counter");
+        current_id
+    });
+
+    COMPANY_NOTIFICATIONS.with(|map| {
+        map.borrow_mut().insert((StorableString { value: comp_username.to_string() }, notification_id), CompanyNotification {
+            kind,
+            employee_id: employee_id.to_string(),
+            created_at: ic_cdk::api::time(),
+        })
+    });
+}
+
+fn company_notification_ids(comp_username: &str) -> Vec<u64> {
+    let comp_key = StorableString { value: comp_username.to_string() };
+
+    COMPANY_NOTIFICATIONS.with(|map| {
+        map.borrow()
+            .range((comp_key.clone(), 0)..=(comp_key, u64::MAX))
+            .map(|((_, notification_id), _)| notification_id)
+            .collect()
+    })
+}
+
+// Newest first
+#[ic_cdk::query]
+fn list_company_notifications(comp_username: String) -> Result<Vec<CompanyNotificationSummary>, &'static str> {
+    if !is_company_admin(&ic_cdk::caller().to_text(), &comp_username) {
+        return Err("Only company admin can view notifications");
+    }
+    let comp_key = StorableString { value: comp_username };
+
+    let mut notifications = COMPANY_NOTIFICATIONS.with(|map| {
+        map.borrow()
+            .range((comp_key.clone(), 0)..=(comp_key, u64::MAX))
+            .map(|((_, notification_id), notification)| CompanyNotificationSummary {
+                notification_id,
+                kind: notification.kind,
+                employee_name: get_employee_name_by_id(&notification.employee_id),
+                employee_id: notification.employee_id,
+                created_at: notification.created_at,
+            })
+            .collect::<Vec<CompanyNotificationSummary>>()
+    });
+    notifications.reverse();
+    Ok(notifications)
+}
+
+#[ic_cdk::update]
+fn dismiss_company_notification(comp_username: String, notification_id: u64) -> Result<(), &'static str> {
+    if !is_company_admin(&ic_cdk::caller().to_text(), &comp_username) {
+        return Err("Only company admin can dismiss notifications");
+    }
+    let key = (StorableString { value: comp_username }, notification_id);
+    COMPANY_NOTIFICATIONS.with(|map| map.borrow_mut().remove(&key))
+        .map(|_| ())
+        .ok_or("Notification not found")
+}
+
+// Takes the employee off the current membership lists and ends the employment record
+fn end_membership(comp_username: &str, emp_id: &str, ended_by: &str) -> Result<(), &'static str> {
+    if get_employment(comp_username, emp_id).is_none_or(|employment| employment.end_date.is_some()) {
+        return Err("Employee not found in this company");
+    }
+
+    // Remove company from EMPLOYEE_COMPANIES (employee -> companies)
+    EMPLOYEE_COMPANIES.with(|emp|{
+        let mut map=emp.borrow_mut();
+        let emp_key = StorableString { value: emp_id.to_string() };
+       
+        if let Some(mut comp_list) = map.get(&emp_key) {
+            if let Some(pos) = comp_list.ids.iter().position(|id| id == comp_username) {
+                comp_list.ids.remove(pos);
+                map.insert(emp_key, comp_list);
+            }
+        }
+    });
 
-    PROOF_MAP.with(|p|{
-        p.borrow_mut().insert(proof_id, cur_proof.clone());
+    // End the employment instead of forgetting it
+    if let Some(mut employment) = get_employment(comp_username, emp_id) {
+        let now = ic_cdk::api::time();
+        let mut positions = employment.positions();
+        for held in positions.iter_mut().filter(|p| p.end_date.is_none()) {
+            held.end_date = Some(now);
+            held.ended_by = Some(ended_by.to_string());
+        }
+        employment.end_date = Some(now);
+        employment.ended_by = Some(ended_by.to_string());
+        employment.positions = Some(positions);
+        put_employment(comp_username, emp_id, employment);
+    }
+
+    Ok(())
+}
+
+// Adds a position held alongside the employee's current ones
+#[ic_cdk::update]
+fn add_employee_position(comp_username: String, emp_id: String, position: String, start_date: Option<u64>) -> Result<(), &'static str> {
+    let admin_id = ic_cdk::caller().to_text();
+    if !can_manage_employee(&admin_id, &comp_username, &emp_id) {
+        return Err("Only company or unit admin can manage positions");
+    }
+    if position.trim().is_empty() {
+        return Err("Position cannot be empty");
+    }
+    if position.len() > MAX_POSITION_LENGTH {
+        return Err("Position is too long");
+    }
+    let now = ic_cdk::api::time();
+    if start_date.is_some_and(|date| date > now) {
+        return Err("Start date cannot be in the future");
+    }
+
+    let mut employment = get_employment(&comp_username, &emp_id)
+        .filter(|employment| employment.end_date.is_none())
+        .ok_or("Employee not found in this company")?;
+    let mut positions = employment.positions();
+    if positions.iter().any(|p| p.end_date.is_none() && p.title == position) {
+        return Err("Employee already holds this position");
+    }
+    if positions.iter().filter(|p| p.end_date.is_none()).count() >= MAX_CURRENT_POSITIONS {
+        return Err("Employee holds too many positions at once");
+    }
+
+    positions.push(EmploymentPosition {
+        title: position.clone(),
+        start_date: Some(start_date.unwrap_or(now)),
+        end_date: None,
+        added_by: Some(admin_id),
+        ended_by: None,
     });
-    Ok(proof_code)
+    employment.position = position;
+    employment.positions = Some(positions);
+    put_employment(&comp_username, &emp_id, employment);
+    Ok(())
+}
+
+// Ends one of the employee's current positions; the last one is ended by remove_employee
+#[ic_cdk::update]
+fn end_employee_position(comp_username: String, emp_id: String, position: String) -> Result<(), &'static str> {
+    let admin_id = ic_cdk::caller().to_text();
+    if !can_manage_employee(&admin_id, &comp_username, &emp_id) {
+        return Err("Only company or unit admin can manage positions");
+    }
+
+    let mut employment = get_employment(&comp_username, &emp_id)
+        .filter(|employment| employment.end_date.is_none())
+        .ok_or("Employee not found in this company")?;
+    let mut positions = employment.positions();
+    let held_count = positions.iter().filter(|p| p.end_date.is_none()).count();
+    let held = positions.iter_mut()
+        .find(|p| p.end_date.is_none() && p.title == position)
+        .ok_or("Employee does not hold this position")?;
+    if held_count == 1 {
+        return Err("Cannot end the only position, remove the employee instead");
+    }
+    held.end_date = Some(ic_cdk::api::time());
+    held.ended_by = Some(admin_id);
+
+    // Keep the compatibility field on a position that is still held
+    employment.position = positions.iter().rev()
+        .find(|p| p.end_date.is_none())
+        .map(|p| p.title.clone())
+        .unwrap_or_default();
+    employment.positions = Some(positions);
+    put_employment(&comp_username, &emp_id, employment);
+    Ok(())
+}
+
+// Normalizes the country code to upper case and the city to trimmed text
+fn validate_attributes(mut attributes: EmploymentAttributes) -> Result<EmploymentAttributes, &'static str> {
+    if let Some(location) = attributes.location.as_mut() {
+        location.country = location.country.trim().to_ascii_uppercase();
+        if location.country.len() != 2 || !location.country.bytes().all(|b| b.is_ascii_uppercase()) {
+            return Err("Country must be a two-letter ISO 3166-1 code");
+        }
+        location.city = location.city.as_deref().map(str::trim).filter(|city| !city.is_empty()).map(str::to_string);
+        if location.city.as_ref().is_some_and(|city| city.len() > MAX_CITY_LENGTH) {
+            return Err("City name is too long");
+        }
+    }
+    if attributes.weekly_hours.is_some_and(|hours| hours == 0 || hours > MAX_WEEKLY_HOURS) {
+        return Err("Weekly hours must be between 1 and 80");
+    }
+    Ok(attributes)
+}
+
+// Replaces every attribute of a current employment; fields left None are cleared
+#[ic_cdk::update]
+fn set_employee_attributes(comp_username: String, emp_id: String, attributes: EmploymentAttributes) -> Result<(), &'static str> {
+    if !can_manage_employee(&ic_cdk::caller().to_text(), &comp_username, &emp_id) {
+        return Err("Only company or unit admin can manage employment attributes");
+    }
+    let attributes = validate_attributes(attributes)?;
+
+    let mut employment = get_employment(&comp_username, &emp_id)
+        .filter(|employment| employment.end_date.is_none())
+        .ok_or("Employee not found in this company")?;
+    employment.attributes = Some(attributes);
+    put_employment(&comp_username, &emp_id, employment);
+    Ok(())
 }
 
+// Every position the employee held at the company, ordered by start date; for company admins and the employee
 #[ic_cdk::query]
-fn list_my_companies() -> Vec<String> {
-    let caller_principal = ic_cdk::caller();
+fn get_position_timeline(comp_username: String, emp_id: String) -> Result<Vec<EmploymentPosition>, &'static str> {
+    let caller_id = ic_cdk::caller().to_text();
+    if caller_id != emp_id && !is_company_admin(&caller_id, &comp_username) {
+        return Err("Only company admin or the employee can view the position timeline");
+    }
 
-    let user_id = StorableString {
-        value : caller_principal.to_text(),
+    let employment = get_employment(&comp_username, &emp_id).ok_or("Employee not found in this company")?;
+    let employment_seq = employment_sequence(&comp_username, &emp_id);
+    let mut timeline = position_timeline(&comp_username, &emp_id, employment_seq, &employment);
+    timeline.sort_by_key(|position| position.start_date);
+    Ok(timeline)
+}
+
+fn get_unit(comp_username: &str, unit_id: u64) -> Option<OrgUnit> {
+    let key = (StorableString { value: comp_username.to_string() }, unit_id);
+    ORG_UNITS.with(|map| map.borrow().get(&key))
+}
+
+fn company_units(comp_username: &str) -> Vec<(u64, OrgUnit)> {
+    let comp_key = StorableString { value: comp_username.to_string() };
+
+    ORG_UNITS.with(|map| {
+        map.borrow()
+            .range((comp_key.clone(), 0)..=(comp_key, u64::MAX))
+            .map(|((_, unit_id), unit)| (unit_id, unit))
+            .collect()
+    })
+}
+
+// The unit followed by its parents up to the top-level unit
+fn unit_ancestry(comp_username: &str, unit_id: u64) -> Vec<OrgUnit> {
+    let mut ancestry = Vec::new();
+    let mut next = Some(unit_id);
+    while let Some(unit) = next.and_then(|unit_id| get_unit(comp_username, unit_id)) {
+        next = unit.parent_id;
+        ancestry.push(unit);
+    }
+    ancestry
+}
+
+fn unit_path(comp_username: &str, unit_id: u64) -> Option<String> {
+    let ancestry = unit_ancestry(comp_username, unit_id);
+    if ancestry.is_empty() {
+        return None;
+    }
+    Some(ancestry.iter().rev().map(|unit| unit.name.as_str()).collect::<Vec<&str>>().join(UNIT_PATH_SEPARATOR))
+}
+
+fn unit_subtree(comp_username: &str, unit_id: u64) -> Vec<u64> {
+    let units = company_units(comp_username);
+    let mut subtree = vec![unit_id];
+    let mut i = 0;
+    while i < subtree.len() {
+        let parent = subtree[i];
+        subtree.extend(units.iter().filter(|(_, unit)| unit.parent_id == Some(parent)).map(|(id, _)| *id));
+        i += 1;
+    }
+    subtree
+}
+
+fn can_manage_unit(principal: &str, comp_username: &str, unit_id: u64) -> bool {
+    is_company_admin(principal, comp_username)
+        || unit_ancestry(comp_username, unit_id).iter().any(|unit| unit.admins.iter().any(|admin| admin == principal))
+}
+
+// Company admins manage everyone, unit admins the employees assigned below them
+fn can_manage_employee(principal: &str, comp_username: &str, emp_id: &str) -> bool {
+    is_company_admin(principal, comp_username)
+        || get_employment(comp_username, emp_id)
+            .and_then(|employment| employment.unit_id)
+            .is_some_and(|unit_id| can_manage_unit(principal, comp_username, unit_id))
+}
+
+fn validate_unit_name(name: &str) -> Result<(), &'static str> {
+    if name.trim().is_empty() {
+        return Err("Unit name cannot be empty");
+    }
+    if name.len() > MAX_UNIT_NAME_LENGTH {
+        return Err("Unit name is too long");
+    }
+    Ok(())
+}
+
+// Top-level units are created by company admins, sub-units also by admins of a unit above them
+#[ic_cdk::update]
+fn create_unit(comp_username: String, name: String, parent_id: Option<u64>) -> Result<u64, &'static str> {
+    let caller_id = ic_cdk::caller().to_text();
+    let may_create = match parent_id {
+        None => is_company_admin(&caller_id, &comp_username),
+        Some(parent_id) => can_manage_unit(&caller_id, &comp_username, parent_id),
     };
-    
-    EMPLOYEE_COMPANIES.with(|map| {
-        let map_ref = map.borrow();
-        match map_ref.get(&user_id) {
-            Some(id_list) => id_list.ids.clone(),
-            None => Vec::new(),
+    if !may_create {
+        return Err("Only company or unit admin can create units");
+    }
+    validate_unit_name(&name)?;
+    if let Some(parent_id) = parent_id {
+        let depth = unit_ancestry(&comp_username, parent_id).len();
+        if depth == 0 {
+            return Err("Parent unit not found");
         }
-    })
+        if depth >= MAX_UNIT_DEPTH {
+            return Err("Units cannot be nested any deeper");
+        }
+    }
+    let parent_path_length = parent_id.and_then(|parent_id| unit_path(&comp_username, parent_id))
+        .map_or(0, |path| path.len() + UNIT_PATH_SEPARATOR.len());
+    if parent_path_length + name.len() > MAX_UNIT_PATH_LENGTH {
+        return Err("Unit path is too long");
+    }
+
+    let unit_id = NEXT_UNIT_ID.with(|next_id| {
+        let mut id = next_id.borrow_mut();
+        let current_id = *id.get();
+        id.set(current_id + 1).expect("Failed to persist the unit ID counter");
+        current_id
+    });
+    ORG_UNITS.with(|map| {
+        map.borrow_mut().insert((StorableString { value: comp_username }, unit_id), OrgUnit {
+            name,
+            parent_id,
+            admins: Vec::new(),
+            created_at: ic_cdk::api::time(),
+        })
+    });
+    Ok(unit_id)
+}
+
+#[ic_cdk::update]
+fn rename_unit(comp_username: String, unit_id: u64, name: String) -> Result<(), &'static str> {
+    if !can_manage_unit(&ic_cdk::caller().to_text(), &comp_username, unit_id) {
+        return Err("Only company or unit admin can rename units");
+    }
+    validate_unit_name(&name)?;
+
+    let mut unit = get_unit(&comp_username, unit_id).ok_or("Unit not found")?;
+    // The new name shows up in the path of every unit below this one
+    let longest_path = unit_subtree(&comp_username, unit_id).into_iter()
+        .filter_map(|id| unit_path(&comp_username, id))
+        .map(|path| path.len())
+        .max()
+        .unwrap_or(0);
+    if longest_path - unit.name.len() + name.len() > MAX_UNIT_PATH_LENGTH {
+        return Err("Unit path is too long");
+    }
+    unit.name = name;
+    ORG_UNITS.with(|map| map.borrow_mut().insert((StorableString { value: comp_username }, unit_id), unit));
+    Ok(())
+}
+
+// Only empty units can be deleted: no sub-units and no current employees
+#[ic_cdk::update]
+fn delete_unit(comp_username: String, unit_id: u64) -> Result<(), &'static str> {
+    if !can_manage_unit(&ic_cdk::caller().to_text(), &comp_username, unit_id) {
+        return Err("Only company or unit admin can delete units");
+    }
+    get_unit(&comp_username, unit_id).ok_or("Unit not found")?;
+    if company_units(&comp_username).iter().any(|(_, unit)| unit.parent_id == Some(unit_id)) {
+        return Err("Unit still has sub-units");
+    }
+    let has_employees = company_employments(&comp_username).iter()
+        .any(|(_, employment)| employment.end_date.is_none() && employment.unit_id == Some(unit_id));
+    if has_employees {
+        return Err("Unit still has employees");
+    }
+
+    ORG_UNITS.with(|map| map.borrow_mut().remove(&(StorableString { value: comp_username }, unit_id)));
+    Ok(())
+}
+
+// Delegates HR work for the unit and everything below it
+#[ic_cdk::update]
+fn set_unit_admins(comp_username: String, unit_id: u64, admins: Vec<String>) -> Result<(), &'static str> {
+    if !is_company_admin(&ic_cdk::caller().to_text(), &comp_username) {
+        return Err("Only company admin can appoint unit admins");
+    }
+    if admins.len() > MAX_UNIT_ADMINS {
+        return Err("Too many unit admins");
+    }
+    if admins.iter().any(|admin| Principal::from_text(admin).is_err()) {
+        return Err("Unit admins must be principals");
+    }
+
+    let mut unit = get_unit(&comp_username, unit_id).ok_or("Unit not found")?;
+    unit.admins = admins;
+    ORG_UNITS.with(|map| map.borrow_mut().insert((StorableString { value: comp_username }, unit_id), unit));
+    Ok(())
 }
 
+// Every unit of the company, for its company and unit admins
 #[ic_cdk::query]
-fn list_my_admin_companies() -> Vec<String> {
-    let caller_principal = ic_cdk::caller();
+fn list_units(comp_username: String) -> Result<Vec<UnitSummary>, &'static str> {
+    let caller_id = ic_cdk::caller().to_text();
+    let units = company_units(&comp_username);
+    let is_unit_admin = units.iter().any(|(_, unit)| unit.admins.contains(&caller_id));
+    if !is_company_admin(&caller_id, &comp_username) && !is_unit_admin {
+        return Err("Only company or unit admin can view units");
+    }
 
-    let user_id = StorableString {
-        value : caller_principal.to_text(),
+    Ok(units.into_iter()
+        .map(|(unit_id, unit)| UnitSummary {
+            unit_id,
+            path: unit_path(&comp_username, unit_id).unwrap_or_default(),
+            name: unit.name,
+            parent_id: unit.parent_id,
+            admins: unit.admins,
+        })
+        .collect())
+}
+
+// Moves a current employee into a unit (None -> no unit); unit admins need to manage both ends of the move,
+// and taking someone out of every unit would leave it outside their reach, so only company admins may
+#[ic_cdk::update]
+fn assign_employee_unit(comp_username: String, emp_id: String, unit_id: Option<u64>) -> Result<(), &'static str> {
+    let caller_id = ic_cdk::caller().to_text();
+    let may_assign = match unit_id {
+        Some(unit_id) => can_manage_employee(&caller_id, &comp_username, &emp_id)
+            && can_manage_unit(&caller_id, &comp_username, unit_id),
+        None => is_company_admin(&caller_id, &comp_username),
     };
-    
-    EMPLOYEE_COMPANIES_ADMIN.with(|map| {
-        let map_ref = map.borrow();
-        match map_ref.get(&user_id) {
-            Some(id_list) => id_list.ids.clone(),
-            None => Vec::new(),
-        }
+    if !may_assign {
+        return Err("Only company or unit admin can assign units");
+    }
+    if unit_id.is_some_and(|unit_id| get_unit(&comp_username, unit_id).is_none()) {
+        return Err("Unit not found");
+    }
+
+    let mut employment = get_employment(&comp_username, &emp_id)
+        .filter(|employment| employment.end_date.is_none())
+        .ok_or("Employee not found in this company")?;
+    employment.unit_id = unit_id;
+    put_employment(&comp_username, &emp_id, employment);
+    Ok(())
+}
+
+fn company_employments(company_username: &str) -> Vec<(String, Employment)> {
+    let comp_key = StorableString { value: company_username.to_string() };
+
+    EMPLOYMENTS.with(|map| {
+        map.borrow()
+            .range((comp_key.clone(), StorableString::default())..)
+            .take_while(|((comp, _), _)| *comp == comp_key)
+            .map(|((_, emp), employment)| (emp.value, employment))
+            .collect()
+    })
+}
+
+// Every company the employee has an employment record at, current or former
+fn employment_company_ids(employee_id: &str) -> Vec<String> {
+    let emp_key = StorableString { value: employee_id.to_string() };
+
+    EMPLOYEE_EMPLOYMENTS.with(|idx| {
+        idx.borrow()
+            .range((emp_key.clone(), StorableString::default())..)
+            .take_while(|((emp, _), _)| *emp == emp_key)
+            .map(|((_, comp), _)| comp.value)
+            .collect()
     })
 }
 
 #[ic_cdk::query]
-fn get_company_name(comp_username: String) -> Result<String, &'static str> {
-    let storable_comp_username = StorableString { value: comp_username };
-    
-    COMPANY_MAP.with(|map| {
-        let map_ref = map.borrow();
-        match map_ref.get(&storable_comp_username) {
-            Some(company) => Ok(company.name.clone()),
-            None => Err("Company not found"),
-        }
+fn list_my_employments() -> Vec<EmploymentSummary> {
+    let user_id = ic_cdk::caller().to_text();
+
+    employment_company_ids(&user_id).into_iter()
+        .flat_map(|comp_username| {
+            let mut employments = past_employments(&comp_username, &user_id);
+            employments.extend(get_employment(&comp_username, &user_id));
+            employments.into_iter().zip(0..)
+                .map(|(employment, employment_seq)| {
+                    employment_summary(comp_username.clone(), user_id.clone(), employment_seq, &employment)
+                })
+                .collect::<Vec<EmploymentSummary>>()
+        })
+        .collect()
+}
+
+// Current and former employees of the company, with every employment of re-hired employees
+#[ic_cdk::query]
+fn list_company_employments(comp_username: String) -> Result<Vec<EmploymentSummary>, &'static str> {
+    if !is_company_admin(&ic_cdk::caller().to_text(), &comp_username) {
+        return Err("Only company admin can view employment history");
+    }
+
+    Ok(company_employments(&comp_username).into_iter()
+        .flat_map(|(emp_id, employment)| {
+            let mut employments = past_employments(&comp_username, &emp_id);
+            employments.push(employment);
+            employments.into_iter().zip(0..)
+                .map(|(employment, employment_seq)| {
+                    employment_summary(comp_username.clone(), emp_id.clone(), employment_seq, &employment)
+                })
+                .collect::<Vec<EmploymentSummary>>()
+        })
+        .collect())
+}
+
+// Proof IDs issued by the employee, oldest first
+fn employee_proof_ids(employee_id: &str) -> Vec<u128> {
+    let emp_key = StorableString { value: employee_id.to_string() };
+
+    EMPLOYEE_PROOFS.with(|idx| {
+        idx.borrow()
+            .range((emp_key.clone(), 0)..=(emp_key, u128::MAX))
+            .map(|((_, proof_id), _)| proof_id)
+            .collect()
     })
 }
 
+// Revokes every proof the employee issued for this company that could still be verified
+fn revoke_employee_proofs(company_username: &str, employee_id: &str, revoked_by: &str) {
+    let now = ic_cdk::api::time();
+
+    PROOF_MAP.with(|mp| {
+        let mut map = mp.borrow_mut();
+
+        for proof_id in employee_proof_ids(employee_id) {
+            if let Some(mut proof) = map.get(&proof_id) {
+                if proof.company_username == company_username && proof.status(now) == ProofStatus::Active {
+                    proof.revoked_at = Some(now);
+                    proof.revoked_by = Some(revoked_by.to_string());
+                    certify_proof(proof_id, Some(&proof));
+                    map.insert(proof_id, proof);
+                }
+            }
+        }
+    });
+}
+
+#[ic_cdk::query]
+fn list_my_proofs(page: Option<Page>) -> ProofPage {
+    let user_id = ic_cdk::caller().to_text();
+    let now = ic_cdk::api::time();
+    let (offset, limit) = page_bounds(page);
+
+    // Newest proofs first
+    let mut proof_ids = employee_proof_ids(&user_id);
+    proof_ids.reverse();
+
+    let proofs = PROOF_MAP.with(|mp| {
+        let map = mp.borrow();
+        proof_ids.iter()
+            .skip(offset)
+            .take(limit)
+            .filter_map(|proof_id| map.get(proof_id).map(|proof| ProofSummary {
+                proof_id: *proof_id,
+                company_username: proof.company_username.clone(),
+                position: proof.position.clone(),
+                created_at: proof.created_at,
+                expires_at: proof.expires_at,
+                status: proof.status(now),
+                disclosed_claims: proof.disclosed_claims(),
+            }))
+            .collect::<Vec<ProofSummary>>()
+    });
+
+    ProofPage {
+        proofs,
+        total: proof_ids.len() as u64,
+    }
+}
+
+// Proof IDs issued under the company, oldest first
+fn company_proof_ids(company_username: &str) -> Vec<u128> {
+    let comp_key = StorableString { value: company_username.to_string() };
+
+    COMPANY_PROOFS.with(|idx| {
+        idx.borrow()
+            .range((comp_key.clone(), 0)..=(comp_key, u128::MAX))
+            .map(|((_, proof_id), _)| proof_id)
+            .collect()
+    })
+}
 
 #[ic_cdk::query]
-fn list_company_employess(comp_username:String) -> Result<Vec<CompanyEmployeeWithName>, &'static str> {
+fn list_company_proofs(comp_username: String, filter: Option<ProofFilter>, page: Option<Page>) -> Result<CompanyProofPage, &'static str> {
     let caller_principal = ic_cdk::caller();
-    
+
     // Check if caller is admin of this company
     if !is_company_admin(&caller_principal.to_text(), &comp_username) {
-        return Err("Only company admin can view employee list");
+        return Err("Only company admin can view company proofs");
     }
-    
-    let comp_key = StorableString {
-        value :comp_username,
+
+    let filter = filter.unwrap_or_default();
+    let now = ic_cdk::api::time();
+    let (offset, limit) = page_bounds(page);
+
+    // Narrow down through the employee index when the filter names an employee
+    let mut proof_ids = match &filter.employee_id {
+        Some(emp_id) => employee_proof_ids(emp_id),
+        None => company_proof_ids(&comp_username),
     };
-    
-    COMPANY_EMPLOYEES.with(|map| {
-        let map_ref = map.borrow();
-        match map_ref.get(&comp_key) {
-            Some(emp_list) => {
-                let enriched = emp_list.employees.iter().map(|e| {
-                    CompanyEmployeeWithName {
-                        employee_id: e.employee_id.clone(),
-                        employee_name: get_employee_name_by_id(&e.employee_id),
-                        position: e.position.clone(),
-                    }
-                }).collect::<Vec<CompanyEmployeeWithName>>();
-                Ok(enriched)
-            },
-            None => Ok(Vec::new()), // Company exists but has no employees yet
-        }
+    proof_ids.reverse();
+
+    let matching = PROOF_MAP.with(|mp| {
+        let map = mp.borrow();
+        proof_ids.into_iter()
+            .filter_map(|proof_id| map.get(&proof_id).map(|proof| (proof_id, proof)))
+            .filter(|(_, proof)| proof.company_username == comp_username)
+            .filter(|(_, proof)| filter.status.is_none_or(|status| proof.status(now) == status))
+            .collect::<Vec<(u128, Proof)>>()
+    });
+
+    let proofs = matching.iter()
+        .skip(offset)
+        .take(limit)
+        .map(|(proof_id, proof)| CompanyProofSummary {
+            proof_id: *proof_id,
+            employee_id: proof.employee_id.clone(),
+            employee_name: get_employee_name_by_id(&proof.employee_id),
+            position: proof.position.clone(),
+            created_at: proof.created_at,
+            expires_at: proof.expires_at,
+            status: proof.status(now),
+            verification_count: proof.verification_count(),
+            max_verifications: proof.max_verifications(),
+            last_verified_at: proof.last_verified_at,
+        })
+        .collect::<Vec<CompanyProofSummary>>();
+
+    Ok(CompanyProofPage {
+        proofs,
+        total: matching.len() as u64,
     })
 }
 
-#[ic_cdk::update]
-fn add_employee(comp_username:String, emp_id:String, position:String) -> Result<(), &'static str> {
-    let caller_principal = ic_cdk::caller();
+#[ic_cdk::query]
+fn list_proof_receipts(proof_id: u128) -> Result<Vec<VerificationReceipt>, &'static str> {
+    let caller_id = ic_cdk::caller().to_text();
 
-    if !is_company_admin(&caller_principal.to_text(), &comp_username) {
-        return Err("Only company admin can add employees");
-    }
+    let proof = PROOF_MAP.with(|mp| mp.borrow().get(&proof_id)).ok_or("Proof not found")?;
 
-    // Validate inputs
-    if emp_id.trim().is_empty() {
-        return Err("Employee ID cannot be empty");
+    // Receipts are visible to the employee who issued the proof and to its company admin
+    if proof.employee_id != caller_id && !is_company_admin(&caller_id, &proof.company_username) {
+        return Err("Only the proof owner or company admin can view verification receipts");
     }
-    if position.trim().is_empty() {
-        return Err("Position cannot be empty");
+
+    Ok(PROOF_RECEIPTS.with(|r| {
+        r.borrow()
+            .range((proof_id, 0)..=(proof_id, u32::MAX))
+            .map(|(_, receipt)| receipt)
+            .collect()
+    }))
+}
+
+fn page_bounds(page: Option<Page>) -> (usize, usize) {
+    match page {
+        Some(page) => (page.offset.min(usize::MAX as u64) as usize, page.limit.clamp(1, MAX_PAGE_LIMIT) as usize),
+        None => (0, DEFAULT_PAGE_LIMIT as usize),
     }
+}
 
-    // Add employee to COMPANY_EMPLOYEES (company -> employees)
-    COMPANY_EMPLOYEES.with(|comp|{
-        let mut map=comp.borrow_mut();
-        let comp_key = StorableString { value: comp_username.clone() };
-       
-        if let Some(mut emp_list) = map.get(&comp_key) {
-            // Check if employee already exists
-            if let Some(existing) = emp_list.employees.iter_mut().find(|e| e.employee_id == emp_id) {
-                // Update position if employee exists
-                existing.position = position.clone(); // ToDo add multiple positions for one employee
-            } else {
-                // Add new employee
-                emp_list.employees.push(CompanyEmployee {
-                    employee_id: emp_id.clone(),
-                    position: position.clone(),
-                });
-            }
-            map.insert(comp_key, emp_list);
-        } else {
-            // Create new employee list
-            let new_list = CompanyEmployeeList {
-                employees: vec![CompanyEmployee {
-                    employee_id: emp_id.clone(),
-                    position: position.clone(),
-                }],
-            };
-            map.insert(comp_key, new_list);
+#[ic_cdk::update]
+fn revoke_proof(proof_id: u128) -> Result<(), &'static str> {
+    let caller_id = ic_cdk::caller().to_text();
+
+    PROOF_MAP.with(|mp| {
+        let mut map = mp.borrow_mut();
+        let mut proof = map.get(&proof_id).ok_or("Proof not found")?;
+
+        // Only the employee who issued the proof or an admin of its company may revoke it
+        if proof.employee_id != caller_id && !is_company_admin(&caller_id, &proof.company_username) {
+            return Err("Only the proof owner or company admin can revoke this proof");
+        }
+        if proof.revoked_at.is_some() {
+            return Err("Proof already revoked");
         }
-    });
 
-    // Add company to EMPLOYEE_COMPANIES (employee -> companies)
-    EMPLOYEE_COMPANIES.with(|emp|{
-        let mut map=emp.borrow_mut();
-        let emp_key = StorableString { value: emp_id.clone() };
-       
-        if let Some(mut comp_list) = map.get(&emp_key) {
-            if !comp_list.ids.contains(&comp_username) {
-                comp_list.ids.push(comp_username.clone());
-                map.insert(emp_key, comp_list);
-            }
-        } else {
-            let new_list = IDList { ids: vec![comp_username.clone()] };
-            map.insert(emp_key, new_list);
+        proof.revoked_at = Some(ic_cdk::api::time());
+        proof.revoked_by = Some(caller_id);
+        certify_proof(proof_id, Some(&proof));
+        map.insert(proof_id, proof);
+        Ok(())
+    })
+}
+
+// Returns the proof ID and the code in the form it was hashed when the proof was issued
+fn parse_proof_code(proof_code: &str) -> Result<(u128, String), &'static str> {
+    if !token::is_token(proof_code) {
+        return Ok((parse_proof_id(proof_code)?, proof_code.to_string()));
+    }
+
+    let token = token::decode(proof_code)?;
+    if token.canister_id != ic_cdk::id().as_slice() {
+        return Err("Proof code was issued by another canister");
+    }
+    Ok((token.proof_id, token::encode(&token)))
+}
+
+// get the secound part of a legacy proof code (ID)
+fn parse_proof_id(proof_code: &str) -> Result<u128, &'static str> {
+    proof_code
+        .get((PROOF_LENTGH as usize)..)
+        .ok_or("Proof code too short")?
+        .parse()
+        .map_err(|_| "Invalid proof ID")
+}
+
+fn hash_proof_code(proof_code: &str, hash_version: Option<u8>) -> String {
+    hex::encode(proof_code_digest(proof_code, hash_version))
+}
+
+fn proof_code_digest(proof_code: &str, hash_version: Option<u8>) -> Vec<u8> {
+    match hash_version {
+        // Unkeyed hash, only kept so proofs issued before PROOF_KEY verify until they expire
+        None => {
+            let mut hasher = Sha256::new();
+            hasher.update(proof_code.as_bytes());
+            hasher.finalize().to_vec()
+        }
+        Some(version) => {
+            let key = PROOF_KEY.with(|k| k.borrow().get().clone());
+            let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("HMAC accepts keys of any length");
+            // Domain-separate the hash per proof version
+            mac.update(format!("proofly-proof-v{}", version).as_bytes());
+            mac.update(&[0]);
+            mac.update(proof_code.as_bytes());
+            mac.finalize().into_bytes().to_vec()
+        }
+    }
+}
+
+// Fetches the canister-held proof key from raw_rand the first time it is needed
+async fn ensure_proof_key() -> Result<(), &'static str> {
+    if PROOF_KEY.with(|k| !k.borrow().get().is_empty()) {
+        return Ok(());
+    }
+
+    let (key,) = ic_cdk::api::management_canister::main::raw_rand()
+        .await
+        .map_err(|_| "Randomness is currently unavailable, please try again")?;
+
+    // Another call may have stored a key while we were waiting; never replace it
+    PROOF_KEY.with(|k| {
+        let mut cell = k.borrow_mut();
+        if cell.get().is_empty() {
+            cell.set(key).expect("Failed to persist the proof key");
         }
     });
-
     Ok(())
 }
 
-#[ic_cdk::update]
-fn remove_employee(comp_username:String, emp_id:String) -> Result<(), &'static str> {
-    let caller_principal = ic_cdk::caller();
-    if !is_company_admin(&caller_principal.to_text(), &comp_username) {
-        return Err("Only company admin can remove employees");
+fn schedule_proof_key_setup() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || {
+        ic_cdk::spawn(async {
+            let _ = ensure_proof_key().await; // generate_proof retries if this fails
+        });
+    });
+}
+
+fn max_failed_attempts() -> u32 {
+    CONFIG.with(|c| c.borrow().get().max_failed_attempts).unwrap_or(DEFAULT_MAX_FAILED_ATTEMPTS)
+}
+
+// Counts a code mismatch and locks the proof for a while once the limit is reached; the caller
+// of this function stores the proof. Counting starts over once a lock has lifted.
+fn record_failed_attempt(proof_id: u128, proof: &mut Proof, now: u64) {
+    if proof.locked_at.is_some() && !proof.is_locked(now) {
+        proof.locked_at = None;
+        proof.failed_attempts = Some(0);
     }
 
-    // Remove employee from COMPANY_EMPLOYEES (company -> employees)
-    COMPANY_EMPLOYEES.with(|comp|{
-        let mut map=comp.borrow_mut();
-        let comp_key = StorableString { value: comp_username.clone() };
-       
-        if let Some(mut emp_list) = map.get(&comp_key) {
-            if let Some(pos) = emp_list.employees.iter().position(|e| e.employee_id == emp_id) {
-                emp_list.employees.remove(pos);
-                map.insert(comp_key, emp_list);
-                Ok(())
-            } else {
-                Err("Employee not found in this company")
-            }
-        } else {
-            Err("Company not found")
-        }
-    })?;
+    let failed_attempts = proof.failed_attempts.unwrap_or(0) + 1;
+    proof.failed_attempts = Some(failed_attempts);
+    if failed_attempts >= max_failed_attempts() {
+        proof.locked_at = Some(now);
+    }
+    certify_proof(proof_id, Some(proof));
+}
 
-    // Remove company from EMPLOYEE_COMPANIES (employee -> companies)
-    EMPLOYEE_COMPANIES.with(|emp|{
-        let mut map=emp.borrow_mut();
-        let emp_key = StorableString { value: emp_id.clone() };
-       
-        if let Some(mut comp_list) = map.get(&emp_key) {
-            if let Some(pos) = comp_list.ids.iter().position(|id| id == &comp_username) {
-                comp_list.ids.remove(pos);
-                map.insert(emp_key, comp_list);
-            }
+// Lifts a lock before it expires, for the employee who issued the proof or a company admin
+#[ic_cdk::update]
+fn unlock_proof(proof_id: u128) -> Result<(), &'static str> {
+    let caller_id = ic_cdk::caller().to_text();
+
+    PROOF_MAP.with(|mp| {
+        let mut map = mp.borrow_mut();
+        let mut proof = map.get(&proof_id).ok_or("Proof not found")?;
+
+        if proof.employee_id != caller_id && !is_company_admin(&caller_id, &proof.company_username) {
+            return Err("Only the proof owner or company admin can unlock this proof");
         }
-    });
 
-    Ok(())
+        proof.locked_at = None;
+        proof.failed_attempts = Some(0);
+        certify_proof(proof_id, Some(&proof));
+        map.insert(proof_id, proof);
+        Ok(())
+    })
+}
+
+// Validates a proof code without consuming a verification or revealing any personal data.
+// A query for immediate feedback, so it cannot record mismatches; the token checksum catches
+// typos, and the lockout is enforced by verify_proof.
+#[ic_cdk::query]
+fn check_proof(proof_code: String) -> Result<ProofCheck, &'static str> {
+    let (proof_id, proof_code) = parse_proof_code(&proof_code)?;
+    let proof = PROOF_MAP.with(|mp| mp.borrow().get(&proof_id)).ok_or("Proof not found")?;
+
+    if proof.is_locked(ic_cdk::api::time()) {
+        return Err("Proof locked after too many failed attempts, try again later");
+    }
+    if !proof.matches_code(&proof_code) {
+        return Err("Proof code mismatch");
+    }
+
+    Ok(ProofCheck {
+        status: proof.status(ic_cdk::api::time()),
+        expires_at: proof.expires_at,
+        remaining_verifications: proof.remaining_verifications(),
+    })
 }
 
 #[ic_cdk::update]
-fn verify_proof(proof_code: String) -> Result<ProofResult, &'static str> {
+fn verify_proof(proof_code: String, purpose: Option<String>) -> Result<ProofResult, &'static str> {
 
-    // get the secound part of proof (ID)
-    let proof_id: u128 = proof_code
-        .get((PROOF_LENTGH as usize)..)
-        .ok_or("Proof code too short")?
-        .parse()
-        .map_err(|_| "Invalid proof ID")?;
+    // Validate input
+    let purpose = purpose.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if purpose.as_ref().is_some_and(|p| p.len() > MAX_PURPOSE_LENGTH) {
+        return Err("Verification purpose is too long");
+    }
+
+    let (proof_id, proof_code) = parse_proof_code(&proof_code)?;
 
     PROOF_MAP.with(|mp|{
         let mut map=mp.borrow_mut();
 
         let mut proof = map.get(&proof_id).ok_or("Proof not found")?;
 
+        // a locked proof stays locked even for the right code, until the lock lifts
+        if proof.is_locked(ic_cdk::api::time()) {
+            return Err("Proof locked after too many failed attempts, try again later");
+        }
+
         if proof.expires_at < ic_cdk::api::time() {
             return Err("Proof expired");                            
         }
 
-        let real_hashed_code=proof.code.clone();
-
-        // get the hash of the proof
-        let mut hasher = Sha256::new();
-        hasher.update(proof_code.as_bytes());
-        let result = hasher.finalize();
-        let hashed_code = hex::encode(result);
-
         // compare the value in the Proof the input value
-        if hashed_code != real_hashed_code {    
+        // Only authenticated verifiers count towards the lockout, so anonymous callers, who all share
+        // one principal, cannot lock proofs for everyone; the 80-bit secret resists guessing on its own
+        if !proof.matches_code(&proof_code) {
+            if ic_cdk::caller() != candid::Principal::anonymous() {
+                record_failed_attempt(proof_id, &mut proof, ic_cdk::api::time());
+                map.insert(proof_id, proof);
+            }
+
             return Err("Proof code mismatch");
         }
 
-        // check if proof is already
-        if proof.is_used {
+        if proof.revoked_at.is_some() {
+            return Err("Proof revoked");
+        }
+
+        // check if the verification budget of the proof is already spent
+        if proof.remaining_verifications() == 0 {
             return Err("Proof already used");
         }
     
-        proof.is_used = true;
+        let now = ic_cdk::api::time();
+        let receipt_index = proof.verification_count();
+        proof.verification_count = Some(receipt_index + 1);
+        proof.last_verified_at = Some(now);
+        certify_proof(proof_id, Some(&proof));
         map.insert(proof_id, proof.clone());
+        let remaining_verifications = proof.remaining_verifications();
+
+        // Keep a receipt of who verified the proof and why
+        let verifier = ic_cdk::caller();
+        let receipt = VerificationReceipt {
+            verifier: if verifier == candid::Principal::anonymous() {
+                String::from("anonymous")
+            } else {
+                verifier.to_text()
+            },
+            verified_at: now,
+            purpose,
+        };
+        PROOF_RECEIPTS.with(|r| {
+            r.borrow_mut().insert((proof_id, receipt_index), receipt);
+        });
         
         // Get company name from COMPANY_MAP
         let company_name = COMPANY_MAP.with(|comp_map| {
@@ -601,14 +2977,26 @@ fn verify_proof(proof_code: String) -> Result<ProofResult, &'static str> {
             }
         });
         
-        // Return ProofResult with complete information
+        let attributes = proof.attributes();
+
+        // Return ProofResult with only the claims the employee chose to disclose
         Ok(ProofResult {
+            company_name: Disclosed::when(proof.discloses(ProofClaim::CompanyName), company_name),
+            employee_id: Disclosed::when(proof.discloses(ProofClaim::EmployeeId), proof.employee_id.clone()),
+            employee_name: Disclosed::when(proof.discloses(ProofClaim::EmployeeName), employee_name),
+            position: Disclosed::when(proof.discloses(ProofClaim::Position), proof.position.clone()),
+            positions: Disclosed::when(proof.discloses(ProofClaim::Position), proof.positions()),
+            held_during: Disclosed::when(proof.discloses(ProofClaim::Position), proof.held_during),
+            created_at: Disclosed::when(proof.discloses(ProofClaim::CreatedAt), proof.created_at),
+            employment: Disclosed::when(proof.discloses(ProofClaim::EmploymentPeriod), proof.employment_period()),
+            unit: Disclosed::when(proof.discloses(ProofClaim::Unit), proof.unit.clone()),
+            employment_type: Disclosed::when(proof.discloses(ProofClaim::EmploymentType), attributes.employment_type),
+            work_location: Disclosed::when(proof.discloses(ProofClaim::WorkLocation), attributes.location),
+            seniority: Disclosed::when(proof.discloses(ProofClaim::Seniority), attributes.seniority),
+            weekly_hours: Disclosed::when(proof.discloses(ProofClaim::WeeklyHours), attributes.weekly_hours),
             company_username: proof.company_username,
-            company_name: company_name,
-            employee_id: proof.employee_id,
-            employee_name: employee_name,
-            position: proof.position,
-            created_at: proof.created_at,
+            expires_at: proof.expires_at,
+            remaining_verifications,
         })
     })
 }
@@ -646,9 +3034,11 @@ fn add_new_companey(comp_username:String, comp_name:String)->Result<(), &'static
         admin_id:admin.clone(),
         created_at:ic_cdk::api::time(),
         is_active:true, // ToDo : subscribtion using payment mathod 
+        max_proof_validity_secs:None,
     };
 
     // insert company in COMPANY_MAP
+    certify_company(&comp_username, Some(&comp));
     COMPANY_MAP.with(|mp| {
         mp.borrow_mut().insert(storable_comp_username.clone(), comp);
     });
@@ -699,6 +3089,40 @@ fn edit_company(comp_username: String, new_comp_name: String) -> Result<(), &'st
             
             // Update company name
             company.name = new_comp_name;
+            certify_company(&comp_username, Some(&company));
+            map.insert(storable_comp_username, company);
+            Ok(())
+        } else {
+            Err("Company not found")
+        }
+    })
+}
+
+#[ic_cdk::update]
+fn set_company_max_proof_validity(comp_username: String, max_validity_secs: Option<u64>) -> Result<(), &'static str> {
+    let caller_principal = ic_cdk::caller();
+
+    // Validate input
+    if let Some(secs) = max_validity_secs {
+        if secs == 0 {
+            return Err("Maximum proof validity must be greater than zero");
+        }
+        if secs > MAX_PROOF_VALIDITY_SECS {
+            return Err("Maximum proof validity exceeds the allowed maximum");
+        }
+    }
+
+    let storable_comp_username = StorableString { value: comp_username };
+
+    COMPANY_MAP.with(|mp| {
+        let mut map = mp.borrow_mut();
+
+        if let Some(mut company) = map.get(&storable_comp_username) {
+            if company.admin_id != caller_principal.to_string() {
+                return Err("Only company admin can edit company details");
+            }
+
+            company.max_proof_validity_secs = max_validity_secs;
             map.insert(storable_comp_username, company);
             Ok(())
         } else {
@@ -707,6 +3131,19 @@ fn edit_company(comp_username: String, new_comp_name: String) -> Result<(), &'st
     })
 }
 
+#[ic_cdk::query]
+fn get_company_max_proof_validity(comp_username: String) -> Result<u64, &'static str> {
+    let storable_comp_username = StorableString { value: comp_username };
+
+    COMPANY_MAP.with(|map| {
+        let map_ref = map.borrow();
+        match map_ref.get(&storable_comp_username) {
+            Some(company) => Ok(company.max_proof_validity_secs.unwrap_or(MAX_PROOF_VALIDITY_SECS)),
+            None => Err("Company not found"),
+        }
+    })
+}
+
 #[ic_cdk::update]
 fn delete_company(comp_username: String) -> Result<(), &'static str> {
     let caller_principal = ic_cdk::caller();
@@ -729,6 +3166,7 @@ fn delete_company(comp_username: String) -> Result<(), &'static str> {
     COMPANY_MAP.with(|mp| {
         mp.borrow_mut().remove(&storable_comp_username);
     });
+    certify_company(&comp_username, None);
 
     // Remove company from admins list in EMPLOYEE_COMPANIES_ADMIN
     let storable_admin = StorableString { value: admin_id };
@@ -745,22 +3183,68 @@ fn delete_company(comp_username: String) -> Result<(), &'static str> {
     });
 
     // Get list of employees in this company
-    let employee_ids = COMPANY_EMPLOYEES.with(|map| {
-        let map_ref = map.borrow();
-        if let Some(emp_list) = map_ref.get(&storable_comp_username) {
-            emp_list.employees.iter()
-                .map(|e| e.employee_id.clone())
-                .collect::<Vec<String>>()
-        } else {
-            Vec::new()
-        }
-    });
+    let employee_ids = company_employments(&comp_username).into_iter()
+        .filter(|(_, employment)| employment.end_date.is_none())
+        .map(|(emp_id, _)| emp_id)
+        .collect::<Vec<String>>();
 
-    // Remove all employees from COMPANY_EMPLOYEES
+    // Remove the roster kept before employment records
     COMPANY_EMPLOYEES.with(|map| {
         map.borrow_mut().remove(&storable_comp_username);
     });
 
+    // Pending invites and employment claims lapse with the company
+    let invited = INVITES.with(|map| {
+        map.borrow()
+            .range((storable_comp_username.clone(), StorableString::default())..)
+            .take_while(|((comp, _), _)| *comp == storable_comp_username)
+            .map(|((_, emp), _)| emp.value)
+            .collect::<Vec<String>>()
+    });
+    for emp_id in invited {
+        take_invite(&comp_username, &emp_id);
+    }
+    let claimants = EMPLOYMENT_CLAIMS.with(|map| {
+        map.borrow()
+            .range((storable_comp_username.clone(), StorableString::default())..)
+            .take_while(|((comp, _), _)| *comp == storable_comp_username)
+            .map(|((_, emp), _)| emp.value)
+            .collect::<Vec<String>>()
+    });
+    for emp_id in claimants {
+        take_claim(&comp_username, &emp_id);
+    }
+
+    for notification_id in company_notification_ids(&comp_username) {
+        COMPANY_NOTIFICATIONS.with(|map| map.borrow_mut().remove(&(storable_comp_username.clone(), notification_id)));
+    }
+
+    for (unit_id, _) in company_units(&comp_username) {
+        ORG_UNITS.with(|map| map.borrow_mut().remove(&(storable_comp_username.clone(), unit_id)));
+    }
+
+    // Employment history belongs to the company and goes with it
+    for (emp_id, _) in company_employments(&comp_username) {
+        let past_count = employment_sequence(&comp_username, &emp_id);
+        let emp_key = StorableString { value: emp_id };
+        for employment_seq in 0..past_count {
+            let key = ((storable_comp_username.clone(), emp_key.clone()), employment_seq);
+            PAST_EMPLOYMENTS.with(|map| map.borrow_mut().remove(&key));
+        }
+        POSITION_HISTORY.with(|map| {
+            let mut map = map.borrow_mut();
+            let key = (storable_comp_username.clone(), emp_key.clone());
+            let position_keys = map.range((key.clone(), (0, 0))..=(key, (u32::MAX, u32::MAX)))
+                .map(|(position_key, _)| position_key)
+                .collect::<Vec<PositionKey>>();
+            for position_key in position_keys {
+                map.remove(&position_key);
+            }
+        });
+        EMPLOYMENTS.with(|map| map.borrow_mut().remove(&(storable_comp_username.clone(), emp_key.clone())));
+        EMPLOYEE_EMPLOYMENTS.with(|idx| idx.borrow_mut().remove(&(emp_key, storable_comp_username.clone())));
+    }
+
     // Remove this company from each employee's company list in EMPLOYEE_COMPANIES
     for emp_id in employee_ids {
         EMPLOYEE_COMPANIES.with(|map| {
@@ -781,9 +3265,430 @@ fn delete_company(comp_username: String) -> Result<(), &'static str> {
     Ok(())
 }
 
+fn certified_proof_status(proof_id: u128, proof: &Proof) -> CertifiedProofStatus {
+    CertifiedProofStatus {
+        proof_id,
+        expires_at: proof.expires_at,
+        remaining_verifications: proof.remaining_verifications(),
+        revoked: proof.revoked_at.is_some(),
+        locked_until: proof.locked_until(),
+        code_sha256: proof.code_sha256(),
+    }
+}
+
+fn certified_company(company: &Company) -> CertifiedCompany {
+    CertifiedCompany {
+        username: company.id.clone(),
+        name: company.name.clone(),
+        created_at: company.created_at,
+        is_active: company.is_active,
+    }
+}
+
+// Must be called whenever a proof in PROOF_MAP is written or removed
+fn certify_proof(proof_id: u128, proof: Option<&Proof>) {
+    let value = proof.map(|p| Encode!(&certified_proof_status(proof_id, p)).unwrap());
+    certification::put(certification::PROOFS_LABEL, proof_id.to_string().into_bytes(), value);
+    certification::publish();
+}
+
+// Must be called whenever a company in COMPANY_MAP is written or removed
+fn certify_company(comp_username: &str, company: Option<&Company>) {
+    let value = company.map(|c| Encode!(&certified_company(c)).unwrap());
+    certification::put(certification::COMPANIES_LABEL, comp_username.as_bytes().to_vec(), value);
+    certification::publish();
+}
+
+fn rebuild_certified_data() {
+    PROOF_MAP.with(|mp| {
+        for (proof_id, proof) in mp.borrow().iter() {
+            let value = Encode!(&certified_proof_status(proof_id, &proof)).unwrap();
+            certification::put(certification::PROOFS_LABEL, proof_id.to_string().into_bytes(), Some(value));
+        }
+    });
+    COMPANY_MAP.with(|mp| {
+        for (comp_key, company) in mp.borrow().iter() {
+            let value = Encode!(&certified_company(&company)).unwrap();
+            certification::put(certification::COMPANIES_LABEL, comp_key.value.into_bytes(), Some(value));
+        }
+    });
+    certification::publish();
+}
+
+// Certified status of the proof behind a code. The leaf commits to SHA-256 of the code, so a client that
+// checks the witness against the certificate knows the status belongs to the code it holds.
+// `value` is a candid-encoded CertifiedProofStatus.
+#[ic_cdk::query]
+fn get_certified_proof_status(proof_code: String) -> Result<Certified, &'static str> {
+    let certificate = ic_cdk::api::data_certificate().ok_or("Certified data is only available in query calls")?;
+    let (proof_id, proof_code) = parse_proof_code(&proof_code)?;
+    let proof = PROOF_MAP.with(|mp| mp.borrow().get(&proof_id)).ok_or("Proof not found")?;
+
+    let code_sha256 = proof.code_sha256().ok_or("Proof was issued before certified status checks")?;
+    if !bool::from(code_sha256.ct_eq(&proof_code_digest(&proof_code, None))) {
+        return Err("Proof code mismatch");
+    }
+
+    let key = proof_id.to_string().into_bytes();
+    Ok(Certified {
+        value: certification::get(certification::PROOFS_LABEL, &key).ok_or("Proof not found")?,
+        certificate,
+        witness: certification::witness(certification::PROOFS_LABEL, &key),
+    })
+}
+
+// `value` is a candid-encoded CertifiedCompany
+#[ic_cdk::query]
+fn get_certified_company(comp_username: String) -> Result<Certified, &'static str> {
+    let certificate = ic_cdk::api::data_certificate().ok_or("Certified data is only available in query calls")?;
+    let key = comp_username.into_bytes();
+
+    Ok(Certified {
+        value: certification::get(certification::COMPANIES_LABEL, &key).ok_or("Company not found")?,
+        certificate,
+        witness: certification::witness(certification::COMPANIES_LABEL, &key),
+    })
+}
+
+#[ic_cdk::query]
+fn get_canister_config() -> CanisterConfig {
+    CONFIG.with(|c| c.borrow().get().clone())
+}
+
+// Fields left as None keep their current value
+#[ic_cdk::update]
+fn update_canister_config(update: CanisterConfig) -> Result<(), &'static str> {
+    if !ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Err("Only canister controllers can update the config");
+    }
+    if update.max_failed_attempts == Some(0) {
+        return Err("Maximum failed attempts must be greater than zero");
+    }
+
+    CONFIG.with(|c| {
+        let mut cell = c.borrow_mut();
+        let mut config = cell.get().clone();
+        if let Some(retention_secs) = update.proof_retention_secs {
+            config.proof_retention_secs = Some(retention_secs);
+        }
+        if let Some(max_failed_attempts) = update.max_failed_attempts {
+            config.max_failed_attempts = Some(max_failed_attempts);
+        }
+        if let Some(key_name) = update.attestation_key_name {
+            config.attestation_key_name = Some(key_name);
+        }
+        cell.set(config).expect("Failed to persist the canister config");
+    });
+
+    Ok(())
+}
+
+fn start_cleanup_timer() {
+    ic_cdk_timers::set_timer_interval(Duration::from_secs(CLEANUP_INTERVAL_SECS), || {
+        cleanup_settled_proofs();
+        cleanup_expired_invites();
+        cleanup_expired_claims();
+    });
+}
+
+// Deletes proofs that stopped being verifiable more than the retention period ago.
+// Each tick resumes where the previous one stopped and yields once the instruction budget is spent;
+// half of the budget goes to the scan, so the deletions it finds always get the other half.
+fn cleanup_settled_proofs() {
+    let now = ic_cdk::api::time();
+    let retention_nanos = CONFIG.with(|c| c.borrow().get().proof_retention_secs)
+        .unwrap_or(DEFAULT_PROOF_RETENTION_SECS)
+        .saturating_mul(1_000_000_000);
+
+    let mut next_cursor = 0;
+    let stale_ids = PROOF_MAP.with(|mp| {
+        let map = mp.borrow();
+        let mut stale_ids = Vec::new();
+        for (proof_id, proof) in map.range(CLEANUP_CURSOR.with(|c| c.get())..) {
+            if ic_cdk::api::instruction_counter() > CLEANUP_INSTRUCTION_BUDGET / 2 {
+                next_cursor = proof_id;
+                break;
+            }
+            if proof.settled_at().saturating_add(retention_nanos) < now {
+                stale_ids.push(proof_id);
+            }
+        }
+        stale_ids
+    });
+
+    for proof_id in stale_ids {
+        if ic_cdk::api::instruction_counter() > CLEANUP_INSTRUCTION_BUDGET {
+            next_cursor = proof_id; // the first proof not deleted
+            break;
+        }
+        delete_proof(proof_id);
+    }
+    CLEANUP_CURSOR.with(|c| c.set(next_cursor));
+}
+
+// Removes the proof together with its index entries and verification receipts
+fn delete_proof(proof_id: u128) {
+    let Some(proof) = PROOF_MAP.with(|mp| mp.borrow_mut().remove(&proof_id)) else {
+        return;
+    };
+    certify_proof(proof_id, None);
+
+    EMPLOYEE_PROOFS.with(|idx| {
+        idx.borrow_mut().remove(&(StorableString { value: proof.employee_id }, proof_id));
+    });
+    COMPANY_PROOFS.with(|idx| {
+        idx.borrow_mut().remove(&(StorableString { value: proof.company_username }, proof_id));
+    });
+    PROOF_RECEIPTS.with(|r| {
+        let mut receipts = r.borrow_mut();
+        let receipt_keys = receipts.range((proof_id, 0)..=(proof_id, u32::MAX))
+            .map(|(key, _)| key)
+            .collect::<Vec<(u128, u32)>>();
+        for key in receipt_keys {
+            receipts.remove(&key);
+        }
+    });
+}
+
+// Drops invites that expired unanswered. Resumes and yields like cleanup_settled_proofs, with the
+// budget counted from its own start so a busy proof cleanup in the same tick cannot starve it.
+fn cleanup_expired_invites() {
+    let now = ic_cdk::api::time();
+    let started_at = ic_cdk::api::instruction_counter();
+    let budget_spent = |share: u64| ic_cdk::api::instruction_counter() - started_at > share;
+
+    let mut next_cursor = None;
+    let expired = INVITES.with(|map| {
+        let map = map.borrow();
+        let mut expired = Vec::new();
+        let cursor = INVITE_CLEANUP_CURSOR.with(|c| c.borrow().clone()).unwrap_or_default();
+        for (key, invite) in map.range(cursor..) {
+            if budget_spent(CLEANUP_INSTRUCTION_BUDGET / 2) {
+                next_cursor = Some(key);
+                break;
+            }
+            if invite.expires_at < now {
+                expired.push(key);
+            }
+        }
+        expired
+    });
+
+    for (comp_key, emp_key) in expired {
+        if budget_spent(CLEANUP_INSTRUCTION_BUDGET) {
+            next_cursor = Some((comp_key, emp_key)); // the first invite not removed
+            break;
+        }
+        take_invite(&comp_key.value, &emp_key.value);
+    }
+    INVITE_CLEANUP_CURSOR.with(|c| *c.borrow_mut() = next_cursor);
+}
+
+// Drops claims nobody reviewed in time, resuming and yielding like cleanup_expired_invites
+fn cleanup_expired_claims() {
+    let now = ic_cdk::api::time();
+    let started_at = ic_cdk::api::instruction_counter();
+    let budget_spent = |share: u64| ic_cdk::api::instruction_counter() - started_at > share;
+
+    let mut next_cursor = None;
+    let expired = EMPLOYMENT_CLAIMS.with(|map| {
+        let map = map.borrow();
+        let mut expired = Vec::new();
+        let cursor = CLAIM_CLEANUP_CURSOR.with(|c| c.borrow().clone()).unwrap_or_default();
+        for (key, claim) in map.range(cursor..) {
+            if budget_spent(CLEANUP_INSTRUCTION_BUDGET / 2) {
+                next_cursor = Some(key);
+                break;
+            }
+            if claim.expires_at() < now {
+                expired.push(key);
+            }
+        }
+        expired
+    });
+
+    for (comp_key, emp_key) in expired {
+        if budget_spent(CLEANUP_INSTRUCTION_BUDGET) {
+            next_cursor = Some((comp_key, emp_key)); // the first claim not removed
+            break;
+        }
+        take_claim(&comp_key.value, &emp_key.value);
+    }
+    CLAIM_CLEANUP_CURSOR.with(|c| *c.borrow_mut() = next_cursor);
+}
+
+#[ic_cdk::init]
+fn init() {
+    certification::publish();
+    schedule_proof_key_setup();
+    start_cleanup_timer();
+}
+
+#[ic_cdk::post_upgrade]
+fn post_upgrade() {
+    // Move proofs out of the original 512-byte map, which had no room for new proof fields
+    LEGACY_PROOF_MAP.with(|legacy| {
+        let mut legacy = legacy.borrow_mut();
+        let legacy_ids = legacy.iter().map(|(proof_id, _)| proof_id).collect::<Vec<u128>>();
+
+        PROOF_MAP.with(|p| {
+            let mut map = p.borrow_mut();
+            for proof_id in legacy_ids {
+                if let Some(LegacyProof(proof)) = legacy.remove(&proof_id) {
+                    map.insert(proof_id, proof);
+                }
+            }
+        });
+    });
+
+    // Releases that kept NEXT_PROOF_ID on the heap restarted it at 0 on every upgrade,
+    // so move the counter past the highest proof ID that is already stored.
+    let next_free_id = PROOF_MAP.with(|p| {
+        p.borrow().last_key_value().map(|(proof_id, _)| proof_id + 1).unwrap_or(0)
+    });
+
+    NEXT_PROOF_ID.with(|next_id| {
+        let mut id = next_id.borrow_mut();
+        if *id.get() < next_free_id {
+            id.set(next_free_id).expect("Failed to persist the proof ID counter");
+        }
+    });
+
+    // Proofs issued before the secondary indexes existed are not indexed yet
+    let employee_index_is_empty = EMPLOYEE_PROOFS.with(|idx| idx.borrow().is_empty());
+    let company_index_is_empty = COMPANY_PROOFS.with(|idx| idx.borrow().is_empty());
+    if employee_index_is_empty || company_index_is_empty {
+        PROOF_MAP.with(|p| {
+            for (proof_id, proof) in p.borrow().iter() {
+                if employee_index_is_empty {
+                    EMPLOYEE_PROOFS.with(|idx| {
+                        idx.borrow_mut().insert((StorableString { value: proof.employee_id.clone() }, proof_id), ());
+                    });
+                }
+                if company_index_is_empty {
+                    COMPANY_PROOFS.with(|idx| {
+                        idx.borrow_mut().insert((StorableString { value: proof.company_username.clone() }, proof_id), ());
+                    });
+                }
+            }
+        });
+    }
+
+    // Employees added before employment history only exist in COMPANY_EMPLOYEES, whose bounded
+    // per-company list cannot hold a real roster; move them out so EMPLOYMENTS is the only roster
+    if EMPLOYMENTS.with(|map| map.borrow().is_empty()) {
+        let current = COMPANY_EMPLOYEES.with(|map| map.borrow().iter().collect::<Vec<_>>());
+        for (comp_key, emp_list) in current {
+            COMPANY_EMPLOYEES.with(|map| map.borrow_mut().remove(&comp_key));
+            for emp in emp_list.employees {
+                put_employment(&comp_key.value, &emp.employee_id, Employment {
+                    position: emp.position,
+                    start_date: None,
+                    end_date: None,
+                    ended_by: None,
+                    positions: None,
+                    unit_id: None,
+                    attributes: None,
+                });
+            }
+        }
+    }
+
+    // The certified tree lives on the heap
+    rebuild_certified_data();
+
+    // Timers do not survive upgrades
+    schedule_proof_key_setup();
+    start_cleanup_timer();
+}
+
 #[ic_cdk::query]
 fn get_principal() -> String {
     ic_cdk::caller().to_text()
 }
 
-ic_cdk::export_candid!();
\ No newline at end of file
+ic_cdk::export_candid!();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(title: &str, start_date: Option<u64>, end_date: Option<u64>) -> EmploymentPosition {
+        EmploymentPosition {
+            title: title.to_string(),
+            start_date,
+            end_date,
+            added_by: None,
+            ended_by: None,
+        }
+    }
+
+    fn employment(positions: Vec<EmploymentPosition>) -> Employment {
+        Employment {
+            position: positions.last().map(|p| p.title.clone()).unwrap_or_default(),
+            start_date: Some(100),
+            end_date: None,
+            ended_by: None,
+            positions: Some(positions),
+            unit_id: None,
+            attributes: None,
+        }
+    }
+
+    #[test]
+    fn held_throughout_needs_the_whole_range() {
+        let range = DateRange { from: 200, to: 300 };
+        assert!(position("Dev", Some(200), Some(300)).held_throughout(&range));
+        assert!(position("Dev", Some(100), None).held_throughout(&range));
+        assert!(!position("Dev", Some(201), None).held_throughout(&range));
+        assert!(!position("Dev", Some(100), Some(299)).held_throughout(&range));
+        assert!(!position("Dev", None, None).held_throughout(&range));
+    }
+
+    #[test]
+    fn change_position_ends_the_held_positions() {
+        let current = employment(vec![
+            position("Dev", Some(100), None),
+            position("Mentor", Some(150), None),
+            position("Intern", Some(50), Some(100)),
+        ]);
+        let changed = change_position(current, "Lead".to_string(), 400, "admin").unwrap();
+
+        let positions = changed.positions();
+        assert_eq!(changed.position, "Lead");
+        assert_eq!(changed.held_positions(), vec!["Lead".to_string()]);
+        for title in ["Dev", "Mentor"] {
+            let ended = positions.iter().find(|p| p.title == title).unwrap();
+            assert_eq!(ended.end_date, Some(400));
+            assert_eq!(ended.ended_by.as_deref(), Some("admin"));
+        }
+        assert_eq!(positions.iter().find(|p| p.title == "Intern").unwrap().end_date, Some(100));
+        let lead = positions.iter().find(|p| p.title == "Lead").unwrap();
+        assert_eq!((lead.start_date, lead.added_by.as_deref()), (Some(400), Some("admin")));
+    }
+
+    #[test]
+    fn change_position_keeps_a_position_already_held() {
+        let current = employment(vec![position("Dev", Some(100), None)]);
+        let changed = change_position(current, "Dev".to_string(), 400, "admin").unwrap();
+        assert_eq!(changed.positions().len(), 1);
+        assert_eq!(changed.positions()[0].end_date, None);
+    }
+
+    #[test]
+    fn change_position_rejects_dates_before_a_held_position() {
+        let current = employment(vec![position("Dev", Some(300), None)]);
+        assert_eq!(
+            change_position(current, "Lead".to_string(), 200, "admin").err(),
+            Some("Effective date precedes a current position")
+        );
+    }
+
+    #[test]
+    fn change_position_is_not_limited_by_past_changes() {
+        let mut current = employment(vec![position("Level 0", Some(100), None)]);
+        for level in 1..=(MAX_CURRENT_POSITIONS as u64 * 2) {
+            current = change_position(current, format!("Level {}", level), 100 + level, "admin").unwrap();
+        }
+        assert_eq!(current.held_positions(), vec![format!("Level {}", MAX_CURRENT_POSITIONS * 2)]);
+    }
+}
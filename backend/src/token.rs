@@ -0,0 +1,235 @@
+// Proof codes as compact tokens: "PF<version>-" followed by the Crockford base32 payload and a check symbol.
+// Version 1 payload: canister ID length (1 byte), canister ID, LEB128 proof ID, secret (remaining bytes).
+
+pub static PREFIX: &str = "PF";
+pub static VERSION: u8 = 1;
+
+static ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+static CHECK_SYMBOLS: &[u8; 5] = b"*~$=U"; // values 32..=36 of the mod 37 check symbol
+
+pub struct ProofToken {
+    pub canister_id: Vec<u8>,
+    pub proof_id: u128,
+    pub secret: Vec<u8>,
+}
+
+// True for anything written in the token format, whatever its version; legacy codes never contain '-'
+pub fn is_token(code: &str) -> bool {
+    let code = code.trim();
+    code.get(..PREFIX.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(PREFIX)) && code.contains('-')
+}
+
+pub fn encode(token: &ProofToken) -> String {
+    let mut payload = vec![token.canister_id.len() as u8];
+    payload.extend_from_slice(&token.canister_id);
+    let mut proof_id = token.proof_id;
+    loop {
+        let byte = (proof_id & 0x7f) as u8;
+        proof_id >>= 7;
+        if proof_id == 0 {
+            payload.push(byte);
+            break;
+        }
+        payload.push(byte | 0x80);
+    }
+    payload.extend_from_slice(&token.secret);
+
+    let symbols = to_symbols(&payload);
+    let body: String = symbols.iter().map(|&value| ALPHABET[value as usize] as char).collect();
+    format!("{}{}-{}{}", PREFIX, VERSION, body, check_symbol(&symbols))
+}
+
+// Validates the check symbol before anything else, so mistyped codes never reach a lookup
+pub fn decode(code: &str) -> Result<ProofToken, &'static str> {
+    let code = code.trim().to_ascii_uppercase();
+    let (version, rest) = code.get(PREFIX.len()..).and_then(|rest| rest.split_once('-')).ok_or("Malformed proof code")?;
+    if version != VERSION.to_string() {
+        return Err("Unsupported proof code version");
+    }
+
+    let rest: Vec<char> = rest.chars().filter(|&c| c != '-').collect();
+    let (&check, body) = rest.split_last().ok_or("Malformed proof code")?;
+    let symbols = body.iter().map(|&c| symbol_value(c)).collect::<Option<Vec<u8>>>()
+        .ok_or("Proof code contains invalid characters")?;
+    if check_symbol(&symbols) != check {
+        return Err("Proof code checksum mismatch");
+    }
+
+    let payload = from_symbols(&symbols);
+    let (&canister_len, payload) = payload.split_first().ok_or("Malformed proof code")?;
+    let canister_len = canister_len as usize;
+    if payload.len() < canister_len {
+        return Err("Malformed proof code");
+    }
+    let (canister_id, payload) = payload.split_at(canister_len);
+
+    let mut proof_id: u128 = 0;
+    let mut consumed = 0;
+    for (i, &byte) in payload.iter().enumerate() {
+        if i >= 19 {
+            return Err("Invalid proof ID");
+        }
+        proof_id |= u128::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            consumed = i + 1;
+            break;
+        }
+    }
+    if consumed == 0 {
+        return Err("Invalid proof ID");
+    }
+
+    Ok(ProofToken {
+        canister_id: canister_id.to_vec(),
+        proof_id,
+        secret: payload[consumed..].to_vec(),
+    })
+}
+
+fn to_symbols(bytes: &[u8]) -> Vec<u8> {
+    let mut symbols = Vec::with_capacity((bytes.len() * 8).div_ceil(5));
+    let (mut buffer, mut bits) = (0u16, 0);
+    for &byte in bytes {
+        buffer = (buffer << 8) | u16::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            symbols.push(((buffer >> bits) & 0x1f) as u8);
+        }
+    }
+    if bits > 0 {
+        symbols.push(((buffer << (5 - bits)) & 0x1f) as u8);
+    }
+    symbols
+}
+
+// Trailing bits that do not fill a byte are padding
+fn from_symbols(symbols: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(symbols.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u16, 0);
+    for &value in symbols {
+        buffer = (buffer << 5) | u16::from(value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    bytes
+}
+
+// Crockford decoding is lenient about the characters people confuse
+fn symbol_value(c: char) -> Option<u8> {
+    let c = match c {
+        'O' => '0',
+        'I' | 'L' => '1',
+        c => c,
+    };
+    ALPHABET.iter().position(|&symbol| symbol as char == c).map(|value| value as u8)
+}
+
+// The payload read as one base32 number, mod 37
+fn check_symbol(symbols: &[u8]) -> char {
+    let value = symbols.iter().fold(0u32, |acc, &value| (acc * 32 + u32::from(value)) % 37) as usize;
+    if value < 32 {
+        ALPHABET[value] as char
+    } else {
+        CHECK_SYMBOLS[value - 32] as char
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(proof_id: u128) -> ProofToken {
+        ProofToken {
+            canister_id: vec![0, 0, 0, 0, 0, 0, 0, 1, 1, 1],
+            proof_id,
+            secret: vec![0x5a; 10],
+        }
+    }
+
+    // A well-formed code around an arbitrary payload
+    fn code_for(payload: &[u8]) -> String {
+        let symbols = to_symbols(payload);
+        let body: String = symbols.iter().map(|&value| ALPHABET[value as usize] as char).collect();
+        format!("{}{}-{}{}", PREFIX, VERSION, body, check_symbol(&symbols))
+    }
+
+    fn assert_round_trip(proof_id: u128) {
+        let original = token(proof_id);
+        let decoded = decode(&encode(&original)).unwrap();
+        assert_eq!(decoded.canister_id, original.canister_id);
+        assert_eq!(decoded.proof_id, proof_id);
+        assert_eq!(decoded.secret, original.secret);
+    }
+
+    #[test]
+    fn round_trips_proof_ids() {
+        for proof_id in [0, 1, 127, 128, 300, u64::MAX as u128, u128::MAX] {
+            assert_round_trip(proof_id);
+        }
+    }
+
+    #[test]
+    fn decodes_lower_case_and_confusable_characters() {
+        let code = encode(&token(42));
+        let decoded = decode(&code.to_lowercase()).unwrap();
+        assert_eq!(decoded.proof_id, 42);
+
+        let (prefix, body) = code.split_once('-').unwrap();
+        let confused = format!("{}-{}", prefix, body.replace('0', "O").replace('1', "l"));
+        assert_eq!(decode(&confused).unwrap().proof_id, 42);
+    }
+
+    #[test]
+    fn rejects_a_single_changed_character() {
+        let code = encode(&token(123_456));
+        let start = code.find('-').unwrap() + 1;
+        for i in start..code.len() {
+            for &symbol in ALPHABET.iter().chain(CHECK_SYMBOLS) {
+                let mut changed = code.clone().into_bytes();
+                if changed[i] == symbol {
+                    continue;
+                }
+                changed[i] = symbol;
+                assert!(decode(&String::from_utf8(changed).unwrap()).is_err(), "changed at {}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_swapped_neighbouring_characters() {
+        let code = encode(&token(123_456));
+        let start = code.find('-').unwrap() + 1;
+        for i in start..code.len() - 2 {
+            let mut swapped = code.clone().into_bytes();
+            if swapped[i] == swapped[i + 1] {
+                continue;
+            }
+            swapped.swap(i, i + 1);
+            assert!(decode(&String::from_utf8(swapped).unwrap()).is_err(), "swapped at {}", i);
+        }
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let code = encode(&token(7)).replacen("PF1-", "PF2-", 1);
+        assert_eq!(decode(&code).err(), Some("Unsupported proof code version"));
+    }
+
+    #[test]
+    fn rejects_a_canister_id_longer_than_the_payload() {
+        assert_eq!(decode(&code_for(&[10, 1, 2, 3])).err(), Some("Malformed proof code"));
+        assert_eq!(decode(&code_for(&[])).err(), Some("Malformed proof code"));
+    }
+
+    #[test]
+    fn legacy_codes_are_not_tokens() {
+        for code in ["PFa8kQz3Xb42", "pf00000000017", "Pf1aaaaaaaa0", "AbCdEfGhJk123456789"] {
+            assert!(!is_token(code), "{}", code);
+        }
+        assert!(is_token(&encode(&token(0))));
+    }
+}
@@ -405,7 +405,7 @@ function CompanyPageContent() {
                 const companyName = 'Ok' in nameResult ? nameResult.Ok : companyUsername;
                 
                 // Get employees from backend
-                const employeesResult = await actor.list_company_employess(companyUsername);
+                const employeesResult = await actor.list_company_employess(companyUsername, []);
                 
                 let employees: Employee[] = [];
                 if ('Ok' in employeesResult) {
@@ -483,15 +483,24 @@ function CompanyPageContent() {
 
         setSaving(true);
         try {
-            if (isEditing && editingEmpId) {
-                // Edit: Remove old and add new (workaround since backend doesn't have direct edit)
-                const removeResult = await actor.remove_employee(companyUsername, editingEmpId);
+            if (isEditing && editingEmpId && editingEmpId === empForm.principalId.trim()) {
+                // Same employee: record a position change, the employment itself continues
+                const result = await actor.change_employee_position(companyUsername, editingEmpId, empForm.position.trim(), []);
+
+                if ('Ok' in result) {
+                    alert("Employee updated successfully!");
+                } else {
+                    alert("Error updating employee: " + result.Err);
+                }
+            } else if (isEditing && editingEmpId) {
+                // Different principal: end the old employment and invite the new one
+                const removeResult = await actor.remove_employee(companyUsername, editingEmpId, []);
                 
                 if ('Ok' in removeResult) {
-                    const addResult = await actor.add_employee(companyUsername, empForm.principalId.trim(), empForm.position.trim());
+                    const addResult = await actor.add_employee(companyUsername, empForm.principalId.trim(), empForm.position.trim(), []);
                     
                     if ('Ok' in addResult) {
-                        alert("Employee updated successfully!");
+                        alert("Employee removed, an invitation was sent to the new principal.");
                     } else {
                         alert("Error updating employee: " + addResult.Err);
                     }
@@ -499,18 +508,18 @@ function CompanyPageContent() {
                     alert("Error: " + removeResult.Err);
                 }
             } else {
-                // Add new employee
-                const result = await actor.add_employee(companyUsername, empForm.principalId.trim(), empForm.position.trim());
+                // Invite new employee, they join once they accept
+                const result = await actor.add_employee(companyUsername, empForm.principalId.trim(), empForm.position.trim(), []);
                 
                 if ('Ok' in result) {
-                    alert("Employee added successfully!");
+                    alert("Invitation sent! The employee appears here once they accept.");
                 } else {
                     alert("Error: " + result.Err);
                 }
             }
             
             // Reload employees from backend
-            const employeesResult = await actor.list_company_employess(companyUsername);
+            const employeesResult = await actor.list_company_employess(companyUsername, []);
             
             if ('Ok' in employeesResult) {
                 const employees: Employee[] = employeesResult.Ok.map((emp: any) => ({
@@ -546,13 +555,13 @@ function CompanyPageContent() {
         }
 
         try {
-            const result = await actor.remove_employee(companyUsername, id);
+            const result = await actor.remove_employee(companyUsername, id, []);
             
             if ('Ok' in result) {
                 alert("Employee deleted successfully!");
                 
                 // Reload employees from backend
-                const employeesResult = await actor.list_company_employess(companyUsername);
+                const employeesResult = await actor.list_company_employess(companyUsername, []);
                 
                 if ('Ok' in employeesResult) {
                     const employees: Employee[] = employeesResult.Ok.map((emp: any) => ({
@@ -590,7 +599,7 @@ function CompanyPageContent() {
             
             for (const id of ids) {
                 try {
-                    const result = await actor.remove_employee(companyUsername, id);
+                    const result = await actor.remove_employee(companyUsername, id, []);
                     if ('Ok' in result) {
                         successCount++;
                     } else {
@@ -602,7 +611,7 @@ function CompanyPageContent() {
             }
             
             // Reload employees from backend
-            const employeesResult = await actor.list_company_employess(companyUsername);
+            const employeesResult = await actor.list_company_employess(companyUsername, []);
             
             if ('Ok' in employeesResult) {
                 const employees: Employee[] = employeesResult.Ok.map((emp: any) => ({
@@ -44,7 +44,7 @@ export default function page() {
                         // Get employees list to count them
                         let employeesCount = 0;
                         try {
-                            const employeesResult = await actor.list_company_employess(username);
+                            const employeesResult = await actor.list_company_employess(username, []);
                             if ('Ok' in employeesResult) {
                                 employeesCount = employeesResult.Ok.length;
                             }
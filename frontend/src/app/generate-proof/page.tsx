@@ -19,6 +19,7 @@ import { Popover, PopoverContent, PopoverTrigger } from '@/components/ui/popover
 import { Command, CommandEmpty, CommandGroup, CommandInput, CommandItem, CommandList } from '@/components/ui/command'
 import { useICPActor } from '@/hooks/useICPActor'
 import ProtectedRoute from "@/components/ProtectedRoute"
+import { type EmploymentSummary } from '@/types/backend'
 
 
 const generateProofCodeSchema = z.object({
@@ -45,19 +46,14 @@ export default function page() {
             if (!actor) return;
             try {
                 setLoadingCompanies(true);
-                const companyUsernames = await actor.list_my_companies() as string[];
-                const companiesWithNames = await Promise.all(
-                    companyUsernames.map(async (username) => {
-                        try {
-                            const nameResult = await actor.get_company_name(username);
-                            const name = 'Ok' in nameResult ? nameResult.Ok : username;
-                            return { username, name };
-                        } catch (error) {
-                            return { username, name: username };
-                        }
-                    })
-                );
-                setCompanies(companiesWithNames);
+                // Former employers are included, their proofs state when the employment ended;
+                // re-hired employees get one entry per employment, so list each company once
+                const employments = await actor.list_my_employments() as EmploymentSummary[];
+                const byUsername = new Map(employments.map((employment) => [employment.company_username, {
+                    username: employment.company_username,
+                    name: employment.company_name,
+                }]));
+                setCompanies([...byUsername.values()]);
             } catch (error) {
                 toastManager.add({
                     title: 'Failed to Load Companies',
@@ -118,13 +114,13 @@ export default function page() {
                 type: "loading",
             });
 
-            const result = await actor.generate_proof(selectedCompanyUsername);
+            const result = await actor.generate_proof(selectedCompanyUsername, []);
 
             toastManager.close(id);
 
             // Check if Result is Ok or Err
             if (result && 'Ok' in result) {
-                const proofCode = result.Ok;
+                const proofCode = result.Ok.proof_code;
                 setGeneratedProof(proofCode);
                 toastManager.add({
                     title: "Proof Generated Successfully",
@@ -19,10 +19,18 @@ import { Avatar, AvatarFallback, AvatarImage } from '@/components/ui/avatar'
 import { Badge } from '@/components/ui/badge'
 import { Empty, EmptyContent, EmptyDescription, EmptyHeader, EmptyMedia, EmptyTitle } from '@/components/ui/empty'
 import { useICPActor } from '@/hooks/useICPActor'
-import type { ProofResult, Result } from '@/types/backend'
+import { disclosedOr, type EmploymentPeriod, type ProofResult, type Result } from '@/types/backend'
 
 const MIN_PROOF_CODE_LENGTH = 11
 
+const formatDate = (nanos: [] | [bigint]) =>
+    nanos.length ? new Date(Number(nanos[0] / BigInt(1_000_000))).toLocaleDateString() : "unknown"
+
+const describeEmployment = (period: EmploymentPeriod) =>
+    'Current' in period.status
+        ? `Currently employed (since ${formatDate(period.start_date)})`
+        : `Employed from ${formatDate(period.start_date)} to ${formatDate(period.end_date)}`
+
 const proofCodeSchema = z.object({
     proofCode: z.string().min(1, "Proof code is required"),
 })
@@ -76,7 +84,7 @@ export default function page() {
             })
 
             // Call backend verify_proof function
-            const result: Result<ProofResult> = await actor.verify_proof(data.proofCode)
+            const result: Result<ProofResult> = await actor.verify_proof(data.proofCode, [])
             
             toastManager.close(id)
 
@@ -228,11 +236,11 @@ export default function page() {
                                                             {/* user */}
                                                             <div className="flex items-center gap-3 flex-wrap">
                                                                 <Avatar className='size-10 shrink-0'>
-                                                                    <AvatarFallback>{proofData.employee_name.substring(0, 2).toUpperCase()}</AvatarFallback>
+                                                                    <AvatarFallback>{disclosedOr(proofData.employee_name, "??").substring(0, 2).toUpperCase()}</AvatarFallback>
                                                                 </Avatar>
                                                                 <div>
-                                                                    <h6 className="text-lg font-semibold text-gray-900 font-matter leading-none">{proofData.employee_name}</h6>
-                                                                    <p className="text-sm text-gray-600 leading-none">{proofData.position}</p>
+                                                                    <h6 className="text-lg font-semibold text-gray-900 font-matter leading-none">{disclosedOr(proofData.employee_name, "Name not disclosed")}</h6>
+                                                                    <p className="text-sm text-gray-600 leading-none">{disclosedOr(proofData.position, "Position not disclosed")}</p>
                                                                 </div>
                                                             </div>
                                                             {/* company */}
@@ -240,12 +248,28 @@ export default function page() {
                                                                 <Badge
                                                                     variant="secondary"
                                                                 >
-                                                                    {proofData.company_name}
+                                                                    {disclosedOr(proofData.company_name, proofData.company_username)}
                                                                 </Badge>
                                                             </div>
                                                         </div>
                                                         <div className="mt-4 text-sm text-gray-600">
-                                                            <p>Created: {new Date(Number(proofData.created_at / BigInt(1_000_000))).toLocaleString()}</p>
+                                                            <p>Created: {'Disclosed' in proofData.created_at ? new Date(Number(proofData.created_at.Disclosed / BigInt(1_000_000))).toLocaleString() : "Not disclosed"}</p>
+                                                            <p>Employment: {'Disclosed' in proofData.employment ? describeEmployment(proofData.employment.Disclosed) : "Not disclosed"}</p>
+                                                            {'Disclosed' in proofData.unit && proofData.unit.Disclosed.length > 0 && (
+                                                                <p>Unit: {proofData.unit.Disclosed[0]}</p>
+                                                            )}
+                                                            {'Disclosed' in proofData.employment_type && proofData.employment_type.Disclosed.length > 0 && (
+                                                                <p>Employment type: {Object.keys(proofData.employment_type.Disclosed[0])[0]}</p>
+                                                            )}
+                                                            {'Disclosed' in proofData.work_location && proofData.work_location.Disclosed.length > 0 && (
+                                                                <p>Location: {[...proofData.work_location.Disclosed[0].city, proofData.work_location.Disclosed[0].country].join(", ")}</p>
+                                                            )}
+                                                            {'Disclosed' in proofData.seniority && proofData.seniority.Disclosed.length > 0 && (
+                                                                <p>Seniority: {Object.keys(proofData.seniority.Disclosed[0])[0]}</p>
+                                                            )}
+                                                            {'Disclosed' in proofData.weekly_hours && proofData.weekly_hours.Disclosed.length > 0 && (
+                                                                <p>Weekly hours: {proofData.weekly_hours.Disclosed[0]}</p>
+                                                            )}
                                                         </div>
 
                                                     </CardContent>
@@ -8,7 +8,10 @@ export interface Proof {
     employee_id: string;
     created_at: bigint;
     expires_at: bigint;
-    is_used: boolean;
+    max_verifications: [] | [number];
+    verification_count: [] | [number];
+    revoked_at: [] | [bigint];
+    revoked_by: [] | [string];
 }
 
 export interface Company {
@@ -30,6 +33,42 @@ export interface CompanyEmployeeWithName {
     employee_id: string;
     employee_name: string;
     position: string;
+    positions: string[];
+    unit: [] | [string];
+    attributes: EmploymentAttributes;
+}
+
+// Matches backend struct EmploymentAttributes: typed terms set by company or unit admins
+export type EmploymentType = { FullTime: null } | { PartTime: null } | { Contractor: null } | { Intern: null };
+
+export type SeniorityLevel =
+    | { Entry: null }
+    | { Junior: null }
+    | { Mid: null }
+    | { Senior: null }
+    | { Lead: null }
+    | { Principal: null }
+    | { Executive: null };
+
+export interface WorkLocation {
+    country: string; // ISO 3166-1 alpha-2
+    city: [] | [string];
+}
+
+export interface EmploymentAttributes {
+    employment_type: [] | [EmploymentType];
+    location: [] | [WorkLocation];
+    seniority: [] | [SeniorityLevel];
+    weekly_hours: [] | [number];
+}
+
+// Matches backend struct UnitSummary: a department or team, nested under parent_id
+export interface UnitSummary {
+    unit_id: bigint;
+    name: string;
+    parent_id: [] | [bigint];
+    path: string;
+    admins: string[];
 }
 
 
@@ -37,13 +76,110 @@ export interface CompanyEmployeeList {
     employees: CompanyEmployeeWithName[];
 }
 
-export interface ProofResult {
+// Matches backend enum Disclosed<T>: claims the employee chose not to reveal are Undisclosed
+export type Disclosed<T> = { Disclosed: T } | { Undisclosed: null };
+
+export const disclosedOr = <T,>(claim: Disclosed<T>, fallback: T): T =>
+    'Disclosed' in claim ? claim.Disclosed : fallback;
+
+// Matches backend struct InviteSummary: pending until the invited principal accepts
+export interface InviteSummary {
     company_username: string;
     company_name: string;
     employee_id: string;
     employee_name: string;
     position: string;
+    start_date: [] | [bigint];
+    invited_by: string;
     created_at: bigint;
+    expires_at: bigint;
+}
+
+// Matches backend struct EmploymentClaimSummary: submitted by the employee, reviewed by a company admin
+export interface EmploymentClaimSummary {
+    company_username: string;
+    company_name: string;
+    employee_id: string;
+    employee_name: string;
+    position: string;
+    start_date: [] | [bigint];
+    submitted_at: bigint;
+    expires_at: bigint;
+}
+
+// Matches backend struct EmploymentClaimPage: one page of the company's review queue
+export interface EmploymentClaimPage {
+    claims: EmploymentClaimSummary[];
+    total: bigint;
+}
+
+export type ClaimReview =
+    | { Approve: null }
+    | { Amend: { position: [] | [string]; start_date: [] | [bigint] } }
+    | { Reject: null };
+
+// Matches backend struct CompanyNotificationSummary, shown to company admins
+export type NotificationKind = { EmployeeLeft: null };
+
+export interface CompanyNotificationSummary {
+    notification_id: bigint;
+    kind: NotificationKind;
+    employee_id: string;
+    employee_name: string;
+    created_at: bigint;
+}
+
+// Matches backend enum EmploymentStatus
+export type EmploymentStatus = { Current: null } | { Former: null };
+
+export interface EmploymentPeriod {
+    status: EmploymentStatus;
+    start_date: [] | [bigint];
+    end_date: [] | [bigint];
+}
+
+export interface EmploymentPosition {
+    title: string;
+    start_date: [] | [bigint];
+    end_date: [] | [bigint];
+    added_by: [] | [string];
+    ended_by: [] | [string];
+}
+
+export interface DateRange {
+    from: bigint;
+    to: bigint;
+}
+
+export interface EmploymentSummary {
+    company_username: string;
+    company_name: string;
+    employee_id: string;
+    employee_name: string;
+    position: string;
+    positions: EmploymentPosition[];
+    unit: [] | [string];
+    attributes: EmploymentAttributes;
+    period: EmploymentPeriod;
+}
+
+export interface ProofResult {
+    company_username: string;
+    company_name: Disclosed<string>;
+    employee_id: Disclosed<string>;
+    employee_name: Disclosed<string>;
+    position: Disclosed<string>;
+    positions: Disclosed<string[]>;
+    held_during: Disclosed<[] | [DateRange]>;
+    created_at: Disclosed<bigint>;
+    employment: Disclosed<EmploymentPeriod>;
+    unit: Disclosed<[] | [string]>;
+    employment_type: Disclosed<[] | [EmploymentType]>;
+    work_location: Disclosed<[] | [WorkLocation]>;
+    seniority: Disclosed<[] | [SeniorityLevel]>;
+    weekly_hours: Disclosed<[] | [number]>;
+    expires_at: bigint;
+    remaining_verifications: number;
 }
 
 export type Result<T> = { Ok: T } | { Err: string };